@@ -0,0 +1,293 @@
+//! Headless simulator, which runs games natively, without a browser.
+//!
+//! Example: `snake-sim --seed 42 --games 1000 --script moves.txt`
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::process;
+
+use rust_js_snake_game::bot::{AStarBot, Bot, GreedyBot, HamiltonianBot};
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::difficulty::Difficulty;
+use rust_js_snake_game::events::DeathCause;
+use rust_js_snake_game::level::Level;
+use rust_js_snake_game::replay::Replay;
+use rust_js_snake_game::topology::Topology;
+use rust_js_snake_game::{Game, GameStatus, Movement};
+
+const USAGE: &str = "\
+Usage: snake-sim [OPTIONS]
+
+Options:
+  --width N        board width (default: 17)
+  --height N       board height (default: 15)
+  --speed F        cells per millisecond (default: 0.006)
+  --length N       initial snake length (default: 3)
+  --toroidal       the snake wraps around the board edges, instead of dying
+  --difficulty S   preset: easy, normal, hard or insane; overrides the board size, speed, snake
+                   length and food
+  --level FILE     plays on a level map; overrides the board size and snake length
+  --seed N         seed of the first game (default: 0)
+  --games N        number of games, with consecutive seeds (default: 1)
+  --timespan F     milliseconds per tick (default: 16.666)
+  --max-ticks N    ticks after which a game is stopped (default: 100000)
+  --script FILE    turns, one `<tick> <top|right|down|left>` per line, queued at that tick
+  --bot NAME       plays with a bot: greedy, astar or hamiltonian; overrides --script
+  --replay FILE    plays a replay exported by the web page; ignores the other options
+  --help           prints this message";
+
+struct Options {
+    width: i32,
+    height: i32,
+    speed: f64,
+    snake_length: i32,
+    topology: Topology,
+    difficulty: Option<Difficulty>,
+    level: Option<Level>,
+    seed: u64,
+    games: u64,
+    timespan: f64,
+    max_ticks: u64,
+    script: HashMap<u64, Movement>,
+    bot: Option<String>,
+    replay: Option<Replay>,
+}
+
+struct Outcome {
+    score: i32,
+    ticks: u64,
+    won: bool,
+    death: Option<&'static str>,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> T {
+    let value = value.unwrap_or_else(|| fail(&format!("missing value for {}", name)));
+    value
+        .parse()
+        .unwrap_or_else(|_| fail(&format!("invalid value for {}: {}", name, value)))
+}
+
+fn read_file(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|error| fail(&format!("{}: {}", path, error)))
+}
+
+fn parse_movement(name: &str) -> Option<Movement> {
+    match name.to_lowercase().as_str() {
+        "top" | "up" => Some(Movement::TOP),
+        "right" => Some(Movement::RIGHT),
+        "down" => Some(Movement::DOWN),
+        "left" => Some(Movement::LEFT),
+        _ => None,
+    }
+}
+
+fn parse_script(path: &str) -> HashMap<u64, Movement> {
+    let mut script = HashMap::new();
+    for (index, line) in read_file(path).lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let tick = parts.next().and_then(|tick| tick.parse().ok());
+        let movement = parts.next().and_then(parse_movement);
+        match (tick, movement, parts.next()) {
+            (Some(tick), Some(movement), None) => {
+                script.insert(tick, movement);
+            }
+            _ => fail(&format!("{}:{}: invalid script line", path, index + 1)),
+        }
+    }
+    script
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        width: 17,
+        height: 15,
+        speed: 0.006,
+        snake_length: 3,
+        topology: Topology::Bounded,
+        difficulty: None,
+        level: None,
+        seed: 0,
+        games: 1,
+        timespan: 1000_f64 / 60_f64,
+        max_ticks: 100_000,
+        script: HashMap::new(),
+        bot: None,
+        replay: None,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => options.width = parse_value(&arg, args.next()),
+            "--height" => options.height = parse_value(&arg, args.next()),
+            "--speed" => options.speed = parse_value(&arg, args.next()),
+            "--length" => options.snake_length = parse_value(&arg, args.next()),
+            "--toroidal" => options.topology = Topology::Toroidal,
+            "--difficulty" => {
+                let name: String = parse_value(&arg, args.next());
+                let difficulty = Difficulty::ALL
+                    .iter()
+                    .find(|difficulty| format!("{:?}", difficulty).eq_ignore_ascii_case(&name))
+                    .unwrap_or_else(|| fail(&format!("invalid difficulty: {}", name)));
+                options.difficulty = Some(*difficulty);
+            }
+            "--level" => {
+                let path: String = parse_value(&arg, args.next());
+                let level = Level::parse(&read_file(&path))
+                    .unwrap_or_else(|error| fail(&format!("{}: {}", path, error)));
+                options.level = Some(level);
+            }
+            "--seed" => options.seed = parse_value(&arg, args.next()),
+            "--games" => options.games = parse_value(&arg, args.next()),
+            "--timespan" => options.timespan = parse_value(&arg, args.next()),
+            "--max-ticks" => options.max_ticks = parse_value(&arg, args.next()),
+            "--script" => options.script = parse_script(&parse_value::<String>(&arg, args.next())),
+            "--bot" => {
+                let name: String = parse_value(&arg, args.next());
+                match name.as_str() {
+                    "greedy" | "astar" | "hamiltonian" => options.bot = Some(name),
+                    _ => fail(&format!("unknown bot: {}", name)),
+                }
+            }
+            "--replay" => {
+                let json = read_file(&parse_value::<String>(&arg, args.next()));
+                let replay = Replay::decode_json(&json).unwrap_or_else(|error| fail(&error));
+                options.replay = Some(replay);
+            }
+            "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => fail(&format!("unknown option: {}", arg)),
+        }
+    }
+    options
+}
+
+fn death_cause(cause: DeathCause) -> &'static str {
+    match cause {
+        DeathCause::Wall { .. } => "wall",
+        DeathCause::Obstacle { .. } => "obstacle",
+        DeathCause::SelfCollision { .. } => "self collision",
+        DeathCause::OtherSnake => "other snake",
+        DeathCause::HeadToHead => "head to head",
+    }
+}
+
+fn outcome(game: &Game, ticks: u64) -> Outcome {
+    Outcome {
+        score: game.score(),
+        ticks,
+        won: game.status() == GameStatus::Won,
+        death: match game.status() {
+            GameStatus::Lost(cause) => Some(death_cause(cause)),
+            _ => None,
+        },
+    }
+}
+
+fn create_bot(options: &Options, game: &Game) -> Option<Box<dyn Bot>> {
+    let bot: Box<dyn Bot> = match options.bot.as_deref()? {
+        "greedy" => Box::new(GreedyBot),
+        "astar" => Box::new(AStarBot),
+        _ => Box::new(HamiltonianBot::for_game(game).unwrap_or_else(|| {
+            fail("the hamiltonian bot requires a board without walls, with an even width or height")
+        })),
+    };
+    Some(bot)
+}
+
+fn simulate(options: &Options, seed: u64) -> Outcome {
+    let config = match options.difficulty {
+        Some(difficulty) => difficulty.config(),
+        None => GameConfig::new(options.width, options.height)
+            .speed(options.speed)
+            .snake_length(options.snake_length),
+    };
+    let config = config.topology(options.topology).seed(seed);
+    // Also with a level, which assumes the rest of the settings, such as the speed, are valid.
+    if let Err(error) = config.validate() {
+        fail(&error.to_string());
+    }
+    // The level overrides the board size and the snake length of the config.
+    let mut game = match &options.level {
+        Some(level) => Game::with_level_config(level, &config, seed),
+        None => Game::with_config(&config).unwrap(),
+    };
+    let mut bot = create_bot(options, &game);
+    let mut ticks = 0;
+    while ticks < options.max_ticks && !game.is_over() {
+        let movement = match &mut bot {
+            Some(bot) => bot.next_move(&game),
+            None => {
+                if let Some(&turn) = options.script.get(&ticks) {
+                    // Rejected turns are just ignored, as they would be when playing.
+                    let _ = game.push_turn(0, turn);
+                }
+                None
+            }
+        };
+        game.process(options.timespan, movement);
+        ticks += 1;
+    }
+    outcome(&game, ticks)
+}
+
+fn play_replay(replay: &Replay) -> Outcome {
+    let mut game = replay.create_game();
+    let mut ticks = 0;
+    while ticks < replay.frame_count() && !game.is_over() {
+        replay.apply_frame(&mut game, ticks);
+        ticks += 1;
+    }
+    outcome(&game, ticks as u64)
+}
+
+fn print_outcome(seed: u64, outcome: &Outcome) {
+    println!(
+        "seed={} score={} ticks={} won={} death={}",
+        seed,
+        outcome.score,
+        outcome.ticks,
+        outcome.won,
+        outcome.death.unwrap_or("none")
+    );
+}
+
+fn main() {
+    let options = parse_options();
+
+    if let Some(replay) = &options.replay {
+        print_outcome(replay.create_game().seed(), &play_replay(replay));
+        return;
+    }
+
+    let mut total_score = 0;
+    let mut best_score = 0;
+    let mut wins = 0;
+    for seed in options.seed..options.seed + options.games {
+        let outcome = simulate(&options, seed);
+        print_outcome(seed, &outcome);
+        total_score += i64::from(outcome.score);
+        best_score = best_score.max(outcome.score);
+        wins += u64::from(outcome.won);
+    }
+    if options.games > 1 {
+        println!(
+            "games={} average_score={:.2} best_score={} wins={}",
+            options.games,
+            total_score as f64 / options.games as f64,
+            best_score,
+            wins
+        );
+    }
+}
@@ -0,0 +1,525 @@
+//! Autopilots, which play the game on a grid of cells.
+//!
+//! The snake can only turn at cell centers, so the bots plan from the cell whose center the head
+//! is going to cross next, and keep returning the movement to perform there until it's crossed.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use wasm_bindgen::prelude::*;
+
+use crate::topology::Topology;
+use crate::{are_equal, Cell, Game, Movement, Vector, EPSILON};
+
+const MOVEMENTS: [Movement; 4] = [
+    Movement::TOP,
+    Movement::RIGHT,
+    Movement::DOWN,
+    Movement::LEFT,
+];
+
+pub trait Bot {
+    /// The movement to pass to the next `Game::process` call.
+    fn next_move(&mut self, game: &Game) -> Option<Movement>;
+}
+
+fn step(cell: Cell, movement: Movement) -> Cell {
+    let Vector { x, y } = movement.direction();
+    (cell.0 + x as i32, cell.1 + y as i32)
+}
+
+fn movement_towards(from: Cell, to: Cell) -> Option<Movement> {
+    MOVEMENTS
+        .iter()
+        .copied()
+        .find(|&movement| step(from, movement) == to)
+}
+
+fn is_inside(game: &Game, cell: Cell) -> bool {
+    cell.0 >= 0 && cell.0 < game.width && cell.1 >= 0 && cell.1 < game.height
+}
+
+/// On toroidal boards, brings the cell back on the board.
+fn wrap_cell(game: &Game, cell: Cell) -> Cell {
+    match game.topology {
+        Topology::Bounded => cell,
+        Topology::Toroidal => (
+            cell.0.rem_euclid(game.width),
+            cell.1.rem_euclid(game.height),
+        ),
+    }
+}
+
+fn cell_of(point: &Vector) -> Cell {
+    (point.x.floor() as i32, point.y.floor() as i32)
+}
+
+/// The cells whose center is covered by the snake, from the tail to the head.
+fn body_cells(snake: &[Vector]) -> Vec<Cell> {
+    let mut cells: Vec<Cell> = Vec::new();
+    for pair in snake.windows(2) {
+        let (start, end) = (&pair[0], &pair[1]);
+        let (from, to) = if are_equal(start.y, end.y) {
+            (start.x, end.x)
+        } else {
+            (start.y, end.y)
+        };
+        let forward = to >= from;
+        // Centers are at `k + 0.5`; walk the ones inside the segment, in the direction of the head.
+        let mut k = if forward {
+            (from - 0.5 - EPSILON).ceil()
+        } else {
+            (from - 0.5 + EPSILON).floor()
+        };
+        loop {
+            let center = k + 0.5;
+            if (forward && center > to + EPSILON) || (!forward && center < to - EPSILON) {
+                break;
+            }
+            let cell = if are_equal(start.y, end.y) {
+                (k as i32, start.y.floor() as i32)
+            } else {
+                (start.x.floor() as i32, k as i32)
+            };
+            if cells.last() != Some(&cell) {
+                cells.push(cell);
+            }
+            k += if forward { 1_f64 } else { -1_f64 };
+        }
+    }
+    cells
+}
+
+/// The cell whose center the head is going to cross next, which is where the next turn happens.
+/// Mirrors `Game::process_movement`, which detects the crossing via rounding.
+fn decision_cell(game: &Game) -> Cell {
+    let head = game.head();
+    let direction = game.direction();
+    let next_center = |position: f64, direction: f64| {
+        if direction > 0_f64 {
+            position.round() as i32
+        } else {
+            position.round() as i32 - 1
+        }
+    };
+    let cell = if are_equal(direction.y, 0_f64) {
+        (next_center(head.x, direction.x), head.y.floor() as i32)
+    } else {
+        (head.x.floor() as i32, next_center(head.y, direction.y))
+    };
+    wrap_cell(game, cell)
+}
+
+/// Snapshot of the game, as seen by the grid-based bots, which steer the first snake; the walls and
+/// the other snakes are treated as obstacles.
+struct Board {
+    width: i32,
+    height: i32,
+    topology: Topology,
+    body: Vec<Cell>,
+    obstacles: HashSet<Cell>,
+    occupied: HashSet<Cell>,
+    decision: Cell,
+    /// The closest food item; there's no food once the board is full.
+    food: Option<Cell>,
+}
+
+impl Board {
+    fn new(game: &Game) -> Board {
+        let decision = decision_cell(game);
+        let mut body: Vec<Cell> = body_cells(game.snake())
+            .into_iter()
+            .map(|cell| wrap_cell(game, cell))
+            .collect();
+        // The head may be a rounding error away from the decision center, which it hasn't crossed.
+        if body.last() == Some(&decision) {
+            body.pop();
+        }
+        let obstacles: HashSet<Cell> = game.snakes()[1..]
+            .iter()
+            .filter(|other| other.alive)
+            .flat_map(|other| body_cells(other.body()))
+            .map(|cell| wrap_cell(game, cell))
+            .chain(game.walls().iter().copied())
+            .collect();
+        let occupied = body.iter().chain(&obstacles).copied().collect();
+        let mut board = Board {
+            width: game.width,
+            height: game.height,
+            topology: game.topology,
+            body,
+            obstacles,
+            occupied,
+            decision,
+            food: None,
+        };
+        board.food = game
+            .foods()
+            .iter()
+            .map(|food| cell_of(&food.position))
+            .min_by_key(|&food| board.distance(decision, food));
+        board
+    }
+
+    fn neighbour(&self, cell: Cell, movement: Movement) -> Cell {
+        let (x, y) = step(cell, movement);
+        match self.topology {
+            Topology::Bounded => (x, y),
+            Topology::Toroidal => (x.rem_euclid(self.width), y.rem_euclid(self.height)),
+        }
+    }
+
+    fn movement_towards(&self, from: Cell, to: Cell) -> Option<Movement> {
+        MOVEMENTS
+            .iter()
+            .copied()
+            .find(|&movement| self.neighbour(from, movement) == to)
+    }
+
+    /// Manhattan distance, across the seam on toroidal boards.
+    fn distance(&self, one: Cell, another: Cell) -> i32 {
+        let shortest = |delta: i32, size: i32| match self.topology {
+            Topology::Bounded => delta.abs(),
+            Topology::Toroidal => delta.abs().min(size - delta.abs()),
+        };
+        shortest(one.0 - another.0, self.width) + shortest(one.1 - another.1, self.height)
+    }
+
+    fn is_free(&self, cell: Cell, occupied: &HashSet<Cell>) -> bool {
+        cell.0 >= 0
+            && cell.0 < self.width
+            && cell.1 >= 0
+            && cell.1 < self.height
+            && !occupied.contains(&cell)
+    }
+
+    fn free_neighbours<'a>(
+        &'a self,
+        cell: Cell,
+        occupied: &'a HashSet<Cell>,
+    ) -> impl Iterator<Item = Cell> + 'a {
+        MOVEMENTS
+            .iter()
+            .map(move |&movement| self.neighbour(cell, movement))
+            .filter(move |&neighbour| self.is_free(neighbour, occupied))
+    }
+
+    /// Number of free cells reachable from the given one.
+    fn area(&self, start: Cell, occupied: &HashSet<Cell>) -> usize {
+        let mut visited: HashSet<Cell> = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(start);
+        queue.push_back(start);
+        while let Some(cell) = queue.pop_front() {
+            for neighbour in self.free_neighbours(cell, occupied) {
+                if visited.insert(neighbour) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        visited.len()
+    }
+
+    /// Last resort: the move leading to the largest free area.
+    fn roomiest_move(&self) -> Option<Movement> {
+        let mut occupied = self.occupied.clone();
+        occupied.insert(self.decision);
+        self.free_neighbours(self.decision, &occupied)
+            .max_by_key(|&neighbour| self.area(neighbour, &occupied))
+            .and_then(|neighbour| self.movement_towards(self.decision, neighbour))
+    }
+
+    /// Breadth-first search; returns the path from `start` (excluded) to `goal` (included), if
+    /// they're different cells.
+    fn bfs(&self, start: Cell, goal: Cell, occupied: &HashSet<Cell>) -> Option<Vec<Cell>> {
+        let mut parents: HashMap<Cell, Cell> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(cell) = queue.pop_front() {
+            if cell == goal && cell != start {
+                return Some(rebuild_path(&parents, start, goal));
+            }
+            for neighbour in self.free_neighbours(cell, occupied) {
+                if neighbour != start && !parents.contains_key(&neighbour) {
+                    parents.insert(neighbour, cell);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        None
+    }
+
+    /// A* search with the distance heuristic; same result format as `bfs()`.
+    fn a_star(&self, start: Cell, goal: Cell, occupied: &HashSet<Cell>) -> Option<Vec<Cell>> {
+        let heuristic = |cell: Cell| self.distance(cell, goal);
+        let mut parents: HashMap<Cell, Cell> = HashMap::new();
+        let mut costs: HashMap<Cell, i32> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(start, 0);
+        open.push(Reverse((heuristic(start), 0, start)));
+        while let Some(Reverse((_, cost, cell))) = open.pop() {
+            if cell == goal && cell != start {
+                return Some(rebuild_path(&parents, start, goal));
+            }
+            if cost > costs[&cell] {
+                continue;
+            }
+            for neighbour in self.free_neighbours(cell, occupied) {
+                let neighbour_cost = cost + 1;
+                if costs
+                    .get(&neighbour)
+                    .is_none_or(|&known| neighbour_cost < known)
+                {
+                    costs.insert(neighbour, neighbour_cost);
+                    parents.insert(neighbour, cell);
+                    open.push(Reverse((
+                        neighbour_cost + heuristic(neighbour),
+                        neighbour_cost,
+                        neighbour,
+                    )));
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether, after following the path, the head can still reach the tail, which guarantees
+    /// that the snake doesn't trap itself.
+    fn is_safe(&self, path: &[Cell]) -> bool {
+        let mut body: VecDeque<Cell> = self.body.iter().copied().collect();
+        for &cell in std::iter::once(&self.decision).chain(path) {
+            body.push_back(cell);
+            if Some(cell) != self.food {
+                body.pop_front();
+            }
+        }
+        let head = *body.back().unwrap();
+        let tail = *body.front().unwrap();
+        let mut occupied: HashSet<Cell> = body.iter().chain(&self.obstacles).copied().collect();
+        occupied.remove(&tail);
+        self.bfs(head, tail, &occupied).is_some()
+    }
+}
+
+fn rebuild_path(parents: &HashMap<Cell, Cell>, start: Cell, goal: Cell) -> Vec<Cell> {
+    let mut path = vec![goal];
+    let mut cell = goal;
+    while let Some(&parent) = parents.get(&cell) {
+        if parent == start {
+            break;
+        }
+        path.push(parent);
+        cell = parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Follows the shortest path to the food, ignoring the consequences.
+#[derive(Default)]
+pub struct GreedyBot;
+
+impl Bot for GreedyBot {
+    fn next_move(&mut self, game: &Game) -> Option<Movement> {
+        let board = Board::new(game);
+        if !board.is_free(board.decision, &board.occupied) {
+            return None;
+        }
+        let path = board
+            .food
+            .and_then(|food| board.bfs(board.decision, food, &board.occupied));
+        match path {
+            Some(path) => board.movement_towards(board.decision, path[0]),
+            None => board.roomiest_move(),
+        }
+    }
+}
+
+/// Follows the A* path to the food, but only if afterwards the snake can still reach its tail;
+/// otherwise, it chases the tail, waiting for a safe path to open up.
+#[derive(Default)]
+pub struct AStarBot;
+
+impl Bot for AStarBot {
+    fn next_move(&mut self, game: &Game) -> Option<Movement> {
+        let board = Board::new(game);
+        if !board.is_free(board.decision, &board.occupied) {
+            return None;
+        }
+        let path = board
+            .food
+            .and_then(|food| board.a_star(board.decision, food, &board.occupied));
+        if let Some(path) = path {
+            if board.is_safe(&path) {
+                return board.movement_towards(board.decision, path[0]);
+            }
+        }
+        let tail = board.body[0];
+        let mut occupied = board.occupied.clone();
+        occupied.remove(&tail);
+        match board.a_star(board.decision, tail, &occupied) {
+            Some(path) => board.movement_towards(board.decision, path[0]),
+            None => board.roomiest_move(),
+        }
+    }
+}
+
+/// Follows a cycle through all the cells, which is guaranteed to fill the board. Such cycle
+/// exists only if at least one of the board sides is even; boards with walls aren't supported,
+/// since the walls can leave no such cycle.
+pub struct HamiltonianBot {
+    width: i32,
+    height: i32,
+    /// Index of the successor of each cell (`y * width + x`) in the cycle.
+    successors: Vec<usize>,
+    predecessors: Vec<usize>,
+    /// Whether the cycle is followed backwards, which is decided on the first move, based on the
+    /// snake direction.
+    reversed: Option<bool>,
+}
+
+impl HamiltonianBot {
+    /// Bot for the board of the game, if it has a cycle through all the cells.
+    pub fn for_game(game: &Game) -> Option<HamiltonianBot> {
+        if !game.walls().is_empty() {
+            return None;
+        }
+        HamiltonianBot::new(game.width, game.height)
+    }
+
+    /// Bot for boards of the given size, without walls; it stops steering on other boards.
+    pub fn new(width: i32, height: i32) -> Option<HamiltonianBot> {
+        if width < 2 || height < 2 || (width % 2 != 0 && height % 2 != 0) {
+            return None;
+        }
+        // The cycle is built with an even number of rows; for odd heights, it's built transposed.
+        let transposed = height % 2 != 0;
+        let (columns, rows) = if transposed {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let next = |x: i32, y: i32| -> Cell {
+            if y == 0 {
+                if x < columns - 1 {
+                    (x + 1, y)
+                } else {
+                    (x, y + 1)
+                }
+            } else if x == 0 {
+                (x, y - 1)
+            } else if y % 2 == 1 {
+                // The last row leads back to the first column, which goes up.
+                if x > 1 || y == rows - 1 {
+                    (x - 1, y)
+                } else {
+                    (x, y + 1)
+                }
+            } else if x < columns - 1 {
+                (x + 1, y)
+            } else {
+                (x, y + 1)
+            }
+        };
+        let mut successors = vec![0; (width * height) as usize];
+        for y in 0..rows {
+            for x in 0..columns {
+                let (next_x, next_y) = next(x, y);
+                let (cell, successor) = if transposed {
+                    ((y, x), (next_y, next_x))
+                } else {
+                    ((x, y), (next_x, next_y))
+                };
+                successors[(cell.1 * width + cell.0) as usize] =
+                    (successor.1 * width + successor.0) as usize;
+            }
+        }
+        let mut predecessors = vec![0; successors.len()];
+        for (index, &successor) in successors.iter().enumerate() {
+            predecessors[successor] = index;
+        }
+        Some(HamiltonianBot {
+            width,
+            height,
+            successors,
+            predecessors,
+            reversed: None,
+        })
+    }
+
+    fn cell(&self, index: usize) -> Cell {
+        (index as i32 % self.width, index as i32 / self.width)
+    }
+
+    fn index(&self, cell: Cell) -> usize {
+        (cell.1 * self.width + cell.0) as usize
+    }
+}
+
+impl Bot for HamiltonianBot {
+    fn next_move(&mut self, game: &Game) -> Option<Movement> {
+        if game.width != self.width || game.height != self.height || !game.walls().is_empty() {
+            return None;
+        }
+        let decision = decision_cell(game);
+        if !is_inside(game, decision) {
+            return None;
+        }
+        let index = self.index(decision);
+        let forward = movement_towards(decision, self.cell(self.successors[index]));
+        let backward = movement_towards(decision, self.cell(self.predecessors[index]));
+        let reversed = match self.reversed {
+            Some(reversed) => reversed,
+            None => {
+                // Don't follow the cycle in the direction opposite to the snake's.
+                let reversed = forward?.direction().is_opposite(&game.direction());
+                self.reversed = Some(reversed);
+                reversed
+            }
+        };
+        if reversed {
+            backward
+        } else {
+            forward
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub enum BotKind {
+    Greedy,
+    AStar,
+    Hamiltonian,
+}
+
+/// Wraps the bots, so that they can be used from JavaScript.
+#[wasm_bindgen]
+pub struct Autopilot {
+    bot: Box<dyn Bot>,
+}
+
+#[wasm_bindgen]
+impl Autopilot {
+    /// Bot steering the first snake of the given game.
+    #[wasm_bindgen(constructor)]
+    pub fn new(kind: BotKind, game: &Game) -> Result<Autopilot, JsValue> {
+        let bot: Box<dyn Bot> =
+            match kind {
+                BotKind::Greedy => Box::new(GreedyBot),
+                BotKind::AStar => Box::new(AStarBot),
+                BotKind::Hamiltonian => match HamiltonianBot::for_game(game) {
+                    Some(bot) => Box::new(bot),
+                    None => return Err(JsValue::from_str(
+                        "the Hamiltonian bot requires a board without walls, with at least one \
+                         even side",
+                    )),
+                },
+            };
+        Ok(Autopilot { bot })
+    }
+
+    pub fn next_move(&mut self, game: &Game) -> Option<Movement> {
+        self.bot.next_move(game)
+    }
+}
@@ -0,0 +1,446 @@
+//! Settings of a new game.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::difficulty::{self, Difficulty, SpeedCurve};
+use crate::food::{BonusRules, FoodKind, FoodRules};
+use crate::power_up::PowerUpRules;
+use crate::topology::Topology;
+use crate::{are_equal, Movement, Vector, DEFAULT_TURN_QUEUE_DEPTH};
+
+/// Longest side of a board, which keeps the number of cells, and the memory tracking them takes, in
+/// check.
+pub const MAX_BOARD_SIDE: i32 = 1000;
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    EmptyBoard { width: i32, height: i32 },
+    BoardTooLarge { width: i32, height: i32 },
+    InvalidSpeed(f64),
+    InvalidSpeedCurve,
+    InvalidDirection { x: f64, y: f64 },
+    InvalidSnakeLength(i32),
+    SnakeTooLong { length: i32, max: i32 },
+    NoPlayers,
+    TooManyPlayers(usize),
+    NoFood,
+    InvalidFoodWeight { kind: usize, weight: f64 },
+    InvalidFoodLifetime { kind: usize, lifetime: f64 },
+    InvalidBonusInterval { min: f64, max: f64 },
+    InvalidBonusLifetime(f64),
+    InvalidBonusScore(i32),
+    NoPowerUpKinds,
+    InvalidPowerUpInterval { min: f64, max: f64 },
+    InvalidPowerUpLifetime(f64),
+    InvalidPowerUpKind(usize),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::EmptyBoard { width, height } => write!(
+                f,
+                "the board must be at least 1x1, but it's {}x{}",
+                width, height
+            ),
+            ConfigError::BoardTooLarge { width, height } => write!(
+                f,
+                "the board must be at most {}x{}, but it's {}x{}",
+                MAX_BOARD_SIDE, MAX_BOARD_SIDE, width, height
+            ),
+            ConfigError::InvalidSpeed(speed) => {
+                write!(f, "the speed must be a positive number, but it's {}", speed)
+            }
+            ConfigError::InvalidSpeedCurve => write!(
+                f,
+                "the speed curve must grow by a non-negative increment every positive step, and \
+                 can't be capped below the speed"
+            ),
+            ConfigError::InvalidDirection { x, y } => write!(
+                f,
+                "the direction must be up, right, down or left, but it's ({}, {})",
+                x, y
+            ),
+            ConfigError::InvalidSnakeLength(length) => {
+                write!(f, "the snake must be at least 1 long, but it's {}", length)
+            }
+            ConfigError::SnakeTooLong { length, max } => write!(
+                f,
+                "the snake is {} long, but at most {} fits on the board",
+                length, max
+            ),
+            ConfigError::NoPlayers => write!(f, "there must be at least one player"),
+            ConfigError::TooManyPlayers(players) => {
+                write!(f, "{} snakes don't fit side by side on the board", players)
+            }
+            ConfigError::NoFood => write!(f, "there must be at least one food item"),
+            ConfigError::InvalidFoodWeight { kind, weight } => write!(
+                f,
+                "the weight of food kind {} must be a positive number, but it's {}",
+                kind, weight
+            ),
+            ConfigError::InvalidFoodLifetime { kind, lifetime } => write!(
+                f,
+                "the lifetime of food kind {} must be a positive number, but it's {}",
+                kind, lifetime
+            ),
+            ConfigError::InvalidBonusInterval { min, max } => write!(
+                f,
+                "the bonus interval must be a range of non-negative numbers, but it's {} to {}",
+                min, max
+            ),
+            ConfigError::InvalidBonusLifetime(lifetime) => write!(
+                f,
+                "the bonus lifetime must be a positive number, but it's {}",
+                lifetime
+            ),
+            ConfigError::InvalidBonusScore(score) => {
+                write!(f, "the bonus score must be at least 1, but it's {}", score)
+            }
+            ConfigError::NoPowerUpKinds => write!(f, "there must be at least one power-up kind"),
+            ConfigError::InvalidPowerUpInterval { min, max } => write!(
+                f,
+                "the power-up interval must be a range of non-negative numbers, but it's {} to {}",
+                min, max
+            ),
+            ConfigError::InvalidPowerUpLifetime(lifetime) => write!(
+                f,
+                "the power-up lifetime must be a positive number, but it's {}",
+                lifetime
+            ),
+            ConfigError::InvalidPowerUpKind(kind) => write!(
+                f,
+                "power-up kind {} must have a positive weight and factor, and a non-negative \
+                 duration",
+                kind
+            ),
+        }
+    }
+}
+
+fn default_players() -> usize {
+    1
+}
+
+fn default_turn_queue_depth() -> usize {
+    DEFAULT_TURN_QUEUE_DEPTH
+}
+
+fn default_food_count() -> usize {
+    1
+}
+
+/// Builder of the settings of a game; the snakes all move in the same direction, and are spread
+/// evenly across the board on the perpendicular axis.
+///
+/// The names of the serialized fields match the ones of the replay format, which embeds them.
+#[wasm_bindgen]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) speed: f64,
+    /// If missing, the speed doesn't change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) speed_curve: Option<SpeedCurve>,
+    pub(crate) snake_length: i32,
+    pub(crate) direction: Vector,
+    #[serde(default = "default_players")]
+    pub(crate) players: usize,
+    #[serde(default)]
+    pub(crate) topology: Topology,
+    #[serde(default = "default_turn_queue_depth")]
+    pub(crate) turn_queue_depth: usize,
+    /// Food items on the board at once.
+    #[serde(default = "default_food_count")]
+    pub(crate) food_count: usize,
+    /// If empty, all the food is of the default kind.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) food_kinds: Vec<FoodKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) bonus: Option<BonusRules>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) power_ups: Option<PowerUpRules>,
+    /// If missing, the game picks a random one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) seed: Option<u64>,
+}
+
+#[wasm_bindgen]
+impl GameConfig {
+    /// A single-player game on a bounded board, with a snake of length 3 moving right.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: i32, height: i32) -> GameConfig {
+        GameConfig {
+            width,
+            height,
+            speed: 0.006,
+            speed_curve: None,
+            snake_length: 3,
+            direction: Movement::RIGHT.direction(),
+            players: default_players(),
+            topology: Topology::Bounded,
+            turn_queue_depth: default_turn_queue_depth(),
+            food_count: default_food_count(),
+            food_kinds: Vec::new(),
+            bonus: None,
+            power_ups: None,
+            seed: None,
+        }
+    }
+
+    /// Cells per millisecond.
+    pub fn speed(mut self, speed: f64) -> GameConfig {
+        self.speed = speed;
+        self
+    }
+
+    /// Makes the speed grow from the one set via `speed()`.
+    pub fn speed_curve(mut self, speed_curve: &SpeedCurve) -> GameConfig {
+        self.speed_curve = Some(*speed_curve);
+        self
+    }
+
+    pub fn snake_length(mut self, snake_length: i32) -> GameConfig {
+        self.snake_length = snake_length;
+        self
+    }
+
+    pub fn direction(mut self, direction: Vector) -> GameConfig {
+        self.direction = direction;
+        self
+    }
+
+    pub fn players(mut self, players: usize) -> GameConfig {
+        self.players = players;
+        self
+    }
+
+    pub fn topology(mut self, topology: Topology) -> GameConfig {
+        self.topology = topology;
+        self
+    }
+
+    pub fn turn_queue_depth(mut self, turn_queue_depth: usize) -> GameConfig {
+        self.turn_queue_depth = turn_queue_depth;
+        self
+    }
+
+    pub fn food_count(mut self, food_count: usize) -> GameConfig {
+        self.food_count = food_count;
+        self
+    }
+
+    /// Adds a kind of food; without any, all the food is of the default kind (see `FoodKind`).
+    pub fn food_kind(mut self, kind: &FoodKind) -> GameConfig {
+        self.food_kinds.push(*kind);
+        self
+    }
+
+    /// Enables the bonus food.
+    pub fn bonus(mut self, bonus: &BonusRules) -> GameConfig {
+        self.bonus = Some(*bonus);
+        self
+    }
+
+    /// Enables the power-ups.
+    pub fn power_ups(mut self, power_ups: &PowerUpRules) -> GameConfig {
+        self.power_ups = Some(power_ups.clone());
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> GameConfig {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The settings of the given difficulty preset, which can be changed further.
+    pub fn preset(difficulty: Difficulty) -> GameConfig {
+        difficulty.config()
+    }
+
+    /// The difficulty presets, as an array of objects with the `difficulty` name and its `config`
+    /// settings, from the easiest.
+    pub fn presets() -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(&difficulty::presets())
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        js_sys::JSON::parse(&json)
+    }
+
+    /// Why the configuration is invalid, as a readable message, if it is.
+    pub fn error_message(&self) -> Option<String> {
+        self.validate().err().map(|error| error.to_string())
+    }
+}
+
+impl GameConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(ConfigError::EmptyBoard {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if self.width > MAX_BOARD_SIDE || self.height > MAX_BOARD_SIDE {
+            return Err(ConfigError::BoardTooLarge {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if !(self.speed.is_finite() && self.speed > 0_f64) {
+            return Err(ConfigError::InvalidSpeed(self.speed));
+        }
+        if let Some(speed_curve) = &self.speed_curve {
+            speed_curve.validate(self.speed)?;
+        }
+        let movements = [
+            Movement::TOP,
+            Movement::RIGHT,
+            Movement::DOWN,
+            Movement::LEFT,
+        ];
+        if !movements
+            .iter()
+            .any(|movement| movement.direction().equal_to(&self.direction))
+        {
+            return Err(ConfigError::InvalidDirection {
+                x: self.direction.x,
+                y: self.direction.y,
+            });
+        }
+        if self.snake_length < 1 {
+            return Err(ConfigError::InvalidSnakeLength(self.snake_length));
+        }
+        let max = self.max_snake_length();
+        if self.snake_length > max {
+            return Err(ConfigError::SnakeTooLong {
+                length: self.snake_length,
+                max,
+            });
+        }
+        if self.players == 0 {
+            return Err(ConfigError::NoPlayers);
+        }
+        if !self.players_fit() {
+            return Err(ConfigError::TooManyPlayers(self.players));
+        }
+        if self.food_count == 0 {
+            return Err(ConfigError::NoFood);
+        }
+        for (kind, food_kind) in self.food_kinds.iter().enumerate() {
+            let weight = food_kind.weight;
+            if !(weight.is_finite() && weight > 0_f64) {
+                return Err(ConfigError::InvalidFoodWeight { kind, weight });
+            }
+            match food_kind.lifetime {
+                Some(lifetime) if !(lifetime.is_finite() && lifetime > 0_f64) => {
+                    return Err(ConfigError::InvalidFoodLifetime { kind, lifetime });
+                }
+                _ => {}
+            }
+        }
+        if let Some(bonus) = &self.bonus {
+            let (min, max) = (bonus.min_interval, bonus.max_interval);
+            if !(min.is_finite() && max.is_finite() && min >= 0_f64 && min <= max) {
+                return Err(ConfigError::InvalidBonusInterval { min, max });
+            }
+            if !(bonus.lifetime.is_finite() && bonus.lifetime > 0_f64) {
+                return Err(ConfigError::InvalidBonusLifetime(bonus.lifetime));
+            }
+            if bonus.max_score < 1 {
+                return Err(ConfigError::InvalidBonusScore(bonus.max_score));
+            }
+        }
+        if let Some(power_ups) = &self.power_ups {
+            power_ups.validate()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn food_rules(&self) -> FoodRules {
+        let kinds = if self.food_kinds.is_empty() {
+            vec![FoodKind::default()]
+        } else {
+            self.food_kinds.clone()
+        };
+        FoodRules {
+            count: self.food_count,
+            kinds,
+            bonus: self.bonus,
+        }
+    }
+
+    fn is_horizontal(&self) -> bool {
+        are_equal(self.direction.y, 0_f64)
+    }
+
+    /// The head position on the axis of the direction, which is the board center.
+    fn center(size: i32) -> f64 {
+        (f64::from(size) / 2_f64).round() - 0.5
+    }
+
+    /// The head position of the given player, on the axis perpendicular to the direction.
+    fn spread(&self, size: i32, player: usize) -> f64 {
+        (f64::from(size) * (player + 1) as f64 / (self.players + 1) as f64).round() - 0.5
+    }
+
+    /// The longest snake whose tail end is still on the board.
+    fn max_snake_length(&self) -> i32 {
+        let (size, forward) = if self.is_horizontal() {
+            (self.width, self.direction.x > 0_f64)
+        } else {
+            (self.height, self.direction.y > 0_f64)
+        };
+        let center = GameConfig::center(size);
+        let room = if forward {
+            center
+        } else {
+            f64::from(size) - center
+        };
+        room.floor() as i32
+    }
+
+    /// Whether each snake has its own row (or column) on the board.
+    fn players_fit(&self) -> bool {
+        let size = if self.is_horizontal() {
+            self.height
+        } else {
+            self.width
+        };
+        // Checked first, so that the positions aren't computed for any number of players.
+        if self.players > size as usize {
+            return false;
+        }
+        let mut previous = 0_f64;
+        for player in 0..self.players {
+            let position = self.spread(size, player);
+            if position <= previous {
+                return false;
+            }
+            previous = position;
+        }
+        previous < f64::from(size)
+    }
+
+    /// The initial heads of the snakes.
+    pub(crate) fn heads(&self) -> Vec<Vector> {
+        (0..self.players)
+            .map(|player| {
+                if self.is_horizontal() {
+                    Vector::new(
+                        GameConfig::center(self.width),
+                        self.spread(self.height, player),
+                    )
+                } else {
+                    Vector::new(
+                        self.spread(self.width, player),
+                        GameConfig::center(self.height),
+                    )
+                }
+            })
+            .collect()
+    }
+}
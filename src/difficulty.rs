@@ -0,0 +1,202 @@
+//! Difficulty: how the speed grows as the game goes on, and the presets of the game settings.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::config::{ConfigError, GameConfig};
+use crate::food::{BonusRules, FoodKind};
+
+/// Milliseconds between the changes of linear curves driven by the time: the speed is constant
+/// within each stretch of a frame, so it changes at fixed times, rather than continuously, for the
+/// outcome not to depend on how the time is split into frames. The steps are too small to notice.
+const TIME_RESOLUTION: f64 = 100_f64;
+
+/// What the speed grows with.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedDriver {
+    /// The highest score among the snakes.
+    Score,
+    /// The game time, in milliseconds.
+    Time,
+}
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CurveShape {
+    /// The speed grows in proportion to the driver.
+    Linear,
+    /// The speed grows all at once, whenever the driver reaches a multiple of the step.
+    Stepped,
+}
+
+/// How the speed grows from the configured one: by `increment` every `step` units of the driver,
+/// up to the cap, if there's one.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpeedCurve {
+    pub driver: SpeedDriver,
+    pub shape: CurveShape,
+    pub step: f64,
+    /// Cells per millisecond.
+    pub increment: f64,
+    /// Highest speed; if missing, the speed keeps growing.
+    pub cap: Option<f64>,
+}
+
+#[wasm_bindgen]
+impl SpeedCurve {
+    pub fn linear(driver: SpeedDriver, step: f64, increment: f64) -> SpeedCurve {
+        SpeedCurve::new(driver, CurveShape::Linear, step, increment)
+    }
+
+    pub fn stepped(driver: SpeedDriver, step: f64, increment: f64) -> SpeedCurve {
+        SpeedCurve::new(driver, CurveShape::Stepped, step, increment)
+    }
+
+    /// The same curve, stopping at the given speed.
+    pub fn capped(mut self, cap: f64) -> SpeedCurve {
+        self.cap = Some(cap);
+        self
+    }
+
+    /// Speed of a game with the given base speed, at the given highest score and game time.
+    pub fn speed_at(&self, base: f64, score: i32, time: f64) -> f64 {
+        let value = match self.driver {
+            SpeedDriver::Score => f64::from(score.max(0)),
+            SpeedDriver::Time => self.time_step_start(time.max(0_f64)),
+        };
+        let steps = match self.shape {
+            CurveShape::Linear => value / self.step,
+            CurveShape::Stepped => (value / self.step).floor(),
+        };
+        let speed = base + self.increment * steps;
+        match self.cap {
+            Some(cap) => speed.min(cap),
+            None => speed,
+        }
+    }
+}
+
+impl SpeedCurve {
+    pub fn new(driver: SpeedDriver, shape: CurveShape, step: f64, increment: f64) -> SpeedCurve {
+        SpeedCurve {
+            driver,
+            shape,
+            step,
+            increment,
+            cap: None,
+        }
+    }
+
+    /// Milliseconds until the speed steps up with the time, for curves driven by the time that
+    /// haven't reached the cap.
+    pub(crate) fn next_step(&self, base: f64, time: f64) -> Option<f64> {
+        if self.driver != SpeedDriver::Time
+            || self
+                .cap
+                .is_some_and(|cap| self.speed_at(base, 0, time) >= cap)
+        {
+            return None;
+        }
+        Some(self.time_step_start(time) + self.time_step() - time)
+    }
+
+    fn time_step(&self) -> f64 {
+        match self.shape {
+            CurveShape::Linear => TIME_RESOLUTION,
+            CurveShape::Stepped => self.step,
+        }
+    }
+
+    /// The start of the time step the given time is in.
+    fn time_step_start(&self, time: f64) -> f64 {
+        (time / self.time_step()).floor() * self.time_step()
+    }
+
+    /// The curve must grow, in positive steps, and can't be capped below the base speed.
+    pub(crate) fn validate(&self, base: f64) -> Result<(), ConfigError> {
+        let cap_valid = match self.cap {
+            Some(cap) => cap.is_finite() && cap >= base,
+            None => true,
+        };
+        if !(self.step.is_finite()
+            && self.step > 0_f64
+            && self.increment.is_finite()
+            && self.increment >= 0_f64
+            && cap_valid)
+        {
+            return Err(ConfigError::InvalidSpeedCurve);
+        }
+        Ok(())
+    }
+}
+
+/// Presets of the game settings, from the most forgiving to the most punishing.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    /// The settings of the preset: board size, speed curve, starting length and food; the rest
+    /// are the `GameConfig` defaults.
+    pub fn config(self) -> GameConfig {
+        match self {
+            // Plenty of room and food, and a speed that grows slowly with the score.
+            Difficulty::Easy => GameConfig::new(21, 17)
+                .speed(0.005)
+                .speed_curve(&SpeedCurve::linear(SpeedDriver::Score, 10_f64, 0.001).capped(0.008))
+                .snake_length(3)
+                .food_count(3),
+            Difficulty::Normal => GameConfig::new(17, 15)
+                .speed(0.006)
+                .speed_curve(&SpeedCurve::stepped(SpeedDriver::Score, 5_f64, 0.001).capped(0.012))
+                .snake_length(3)
+                .bonus(&BonusRules::new(10000_f64, 20000_f64, 5000_f64, 5, 1)),
+            // Rich food that goes away if it isn't eaten in time.
+            Difficulty::Hard => GameConfig::new(15, 13)
+                .speed(0.008)
+                .speed_curve(&SpeedCurve::stepped(SpeedDriver::Score, 3_f64, 0.0015).capped(0.018))
+                .snake_length(4)
+                .food_kind(&FoodKind::default())
+                .food_kind(&FoodKind::new(3, 2, Some(5000_f64), 0.25))
+                .bonus(&BonusRules::new(8000_f64, 15000_f64, 3000_f64, 8, 2)),
+            // A small board, food that expires, and a speed that keeps growing with the time.
+            Difficulty::Insane => GameConfig::new(13, 11)
+                .speed(0.01)
+                .speed_curve(&SpeedCurve::linear(SpeedDriver::Time, 10000_f64, 0.001))
+                .snake_length(5)
+                .food_kind(&FoodKind::new(1, 1, Some(8000_f64), 1_f64))
+                .food_kind(&FoodKind::new(5, 3, Some(3000_f64), 0.2)),
+        }
+    }
+}
+
+/// What the page lists of a preset.
+#[derive(Serialize)]
+pub(crate) struct Preset {
+    pub difficulty: Difficulty,
+    pub config: GameConfig,
+}
+
+pub(crate) fn presets() -> Vec<Preset> {
+    Difficulty::ALL
+        .iter()
+        .map(|&difficulty| Preset {
+            difficulty,
+            config: difficulty.config(),
+        })
+        .collect()
+}
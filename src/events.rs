@@ -0,0 +1,168 @@
+use crate::{Cell, Vector};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// Edge of the board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// The snake left a bounded board, across the given edge.
+    Wall { side: Side },
+    /// The snake hit the given wall cell.
+    Obstacle { cell: Cell },
+    /// The head, at `point`, hit the body segment from `body()[segment_index]` to the next point.
+    SelfCollision { segment_index: usize, point: Vector },
+    /// The snake hit the body of another snake.
+    OtherSnake,
+    /// The snake hit the head of another snake, which died too.
+    HeadToHead,
+}
+
+impl DeathCause {
+    pub fn kind(&self) -> DeathCauseKind {
+        match self {
+            DeathCause::Wall { .. } => DeathCauseKind::Wall,
+            DeathCause::Obstacle { .. } => DeathCauseKind::Obstacle,
+            DeathCause::SelfCollision { .. } => DeathCauseKind::SelfCollision,
+            DeathCause::OtherSnake => DeathCauseKind::OtherSnake,
+            DeathCause::HeadToHead => DeathCauseKind::HeadToHead,
+        }
+    }
+}
+
+/// `DeathCause` without the details, which JS has always known as `DeathCause`.
+#[wasm_bindgen(js_name = DeathCause)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeathCauseKind {
+    Wall,
+    Obstacle,
+    SelfCollision,
+    OtherSnake,
+    HeadToHead,
+}
+
+/// Something that happened during `Game::process()`; `time` is the milliseconds elapsed from the
+/// start of the processed timespan.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type")]
+pub enum GameEvent {
+    FoodEaten {
+        time: f64,
+        player: usize,
+        position: Vector,
+        /// Index of the food kind; see `Food::kind`.
+        kind: usize,
+        new_score: i32,
+    },
+    FoodSpawned {
+        time: f64,
+        position: Vector,
+        kind: usize,
+    },
+    /// A food item disappeared at the end of its lifetime.
+    FoodExpired {
+        time: f64,
+        position: Vector,
+    },
+    BonusSpawned {
+        time: f64,
+        position: Vector,
+    },
+    BonusEaten {
+        time: f64,
+        player: usize,
+        position: Vector,
+        /// Points the bonus was worth.
+        score: i32,
+        new_score: i32,
+    },
+    /// The bonus disappeared, uneaten.
+    BonusExpired {
+        time: f64,
+        position: Vector,
+    },
+    PowerUpSpawned {
+        time: f64,
+        position: Vector,
+        /// Index of the power-up kind; see `PowerUp::kind`.
+        kind: usize,
+    },
+    PowerUpCollected {
+        time: f64,
+        player: usize,
+        position: Vector,
+        kind: usize,
+    },
+    /// The power-up disappeared, uncollected.
+    PowerUpExpired {
+        time: f64,
+        position: Vector,
+    },
+    /// The effect of a power-up of the given kind, collected by the player, ran out.
+    EffectEnded {
+        time: f64,
+        player: usize,
+        kind: usize,
+    },
+    Turned {
+        time: f64,
+        player: usize,
+        at: Vector,
+        direction: Vector,
+    },
+    Died {
+        time: f64,
+        player: usize,
+        cause: DeathCause,
+    },
+    /// The board is full.
+    Won {
+        time: f64,
+    },
+}
+
+impl GameEvent {
+    pub fn time(&self) -> f64 {
+        match self {
+            GameEvent::FoodEaten { time, .. }
+            | GameEvent::FoodSpawned { time, .. }
+            | GameEvent::FoodExpired { time, .. }
+            | GameEvent::BonusSpawned { time, .. }
+            | GameEvent::BonusEaten { time, .. }
+            | GameEvent::BonusExpired { time, .. }
+            | GameEvent::PowerUpSpawned { time, .. }
+            | GameEvent::PowerUpCollected { time, .. }
+            | GameEvent::PowerUpExpired { time, .. }
+            | GameEvent::EffectEnded { time, .. }
+            | GameEvent::Turned { time, .. }
+            | GameEvent::Died { time, .. }
+            | GameEvent::Won { time } => *time,
+        }
+    }
+
+    /// Moves the event later by the given time, for frames that are part of a longer one.
+    pub(crate) fn delay(&mut self, by: f64) {
+        match self {
+            GameEvent::FoodEaten { time, .. }
+            | GameEvent::FoodSpawned { time, .. }
+            | GameEvent::FoodExpired { time, .. }
+            | GameEvent::BonusSpawned { time, .. }
+            | GameEvent::BonusEaten { time, .. }
+            | GameEvent::BonusExpired { time, .. }
+            | GameEvent::PowerUpSpawned { time, .. }
+            | GameEvent::PowerUpCollected { time, .. }
+            | GameEvent::PowerUpExpired { time, .. }
+            | GameEvent::EffectEnded { time, .. }
+            | GameEvent::Turned { time, .. }
+            | GameEvent::Died { time, .. }
+            | GameEvent::Won { time } => *time += by,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! The food on the board, and its kinds.
+
+use rand::Rng;
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::Vector;
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FoodKind {
+    /// Points added to the score of the snake eating it.
+    pub score: i32,
+    /// Units the snake grows by.
+    pub growth: u32,
+    /// Milliseconds before an uneaten item disappears; if missing, it stays until eaten.
+    pub lifetime: Option<f64>,
+    /// How likely the kind is to be picked when an item spawns, relative to the other kinds.
+    pub weight: f64,
+}
+
+#[wasm_bindgen]
+impl FoodKind {
+    #[wasm_bindgen(constructor)]
+    pub fn new(score: i32, growth: u32, lifetime: Option<f64>, weight: f64) -> FoodKind {
+        FoodKind {
+            score,
+            growth,
+            lifetime,
+            weight,
+        }
+    }
+}
+
+/// One point and one unit of growth, forever.
+impl Default for FoodKind {
+    fn default() -> FoodKind {
+        FoodKind::new(1, 1, None, 1_f64)
+    }
+}
+
+/// A food item on the board.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Food {
+    /// Always a cell center.
+    pub position: Vector,
+    /// Index of the kind, in the order they've been added to the config.
+    pub kind: usize,
+    /// Milliseconds before the item disappears, if its kind has a lifetime.
+    pub remaining: Option<f64>,
+}
+
+/// Bonus food, which appears at random intervals, one item at a time, and disappears if it isn't
+/// eaten in time; the sooner it's eaten, the more it's worth.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BonusRules {
+    /// Milliseconds from the start of the game, or from the last item going away, until the next
+    /// item appears; picked at random between the two.
+    pub min_interval: f64,
+    pub max_interval: f64,
+    /// Milliseconds an item stays on the board.
+    pub lifetime: f64,
+    /// Score of an item eaten as soon as it appears; it drops to 1 as the time runs out.
+    pub max_score: i32,
+    /// Units the snake grows by.
+    pub growth: u32,
+}
+
+#[wasm_bindgen]
+impl BonusRules {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        min_interval: f64,
+        max_interval: f64,
+        lifetime: f64,
+        max_score: i32,
+        growth: u32,
+    ) -> BonusRules {
+        BonusRules {
+            min_interval,
+            max_interval,
+            lifetime,
+            max_score,
+            growth,
+        }
+    }
+
+    /// Score of an item eaten with the given milliseconds left.
+    pub fn score(&self, remaining: f64) -> i32 {
+        let left = (remaining / self.lifetime).clamp(0_f64, 1_f64);
+        1 + (f64::from(self.max_score - 1) * left).round() as i32
+    }
+}
+
+impl BonusRules {
+    pub(crate) fn next_interval(&self, rng: &mut Pcg32) -> f64 {
+        random_interval(self.min_interval, self.max_interval, rng)
+    }
+}
+
+/// A bonus item on the board.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Bonus {
+    /// Always a cell center.
+    pub position: Vector,
+    /// Milliseconds before the item disappears.
+    pub remaining: f64,
+}
+
+/// How many food items are on the board at once, and of which kinds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FoodRules {
+    pub count: usize,
+    /// Never empty.
+    pub kinds: Vec<FoodKind>,
+    /// If missing, there's no bonus food.
+    #[serde(default)]
+    pub bonus: Option<BonusRules>,
+}
+
+impl Default for FoodRules {
+    fn default() -> FoodRules {
+        FoodRules {
+            count: 1,
+            kinds: vec![FoodKind::default()],
+            bonus: None,
+        }
+    }
+}
+
+impl FoodRules {
+    /// A new item of a random kind, at the given position.
+    pub(crate) fn create_food(&self, position: Vector, rng: &mut Pcg32) -> Food {
+        let kind = pick_weighted(self.kinds.iter().map(|kind| kind.weight), rng);
+        Food {
+            position,
+            kind,
+            remaining: self.kinds[kind].lifetime,
+        }
+    }
+}
+
+/// A random time between the two, which may be equal.
+pub(crate) fn random_interval(min: f64, max: f64, rng: &mut Pcg32) -> f64 {
+    if max > min {
+        rng.gen_range(min, max)
+    } else {
+        min
+    }
+}
+
+/// Picks an index, with a likelihood proportional to its weight.
+pub(crate) fn pick_weighted<I>(weights: I, rng: &mut Pcg32) -> usize
+where
+    I: Iterator<Item = f64> + Clone,
+{
+    let count = weights.clone().count();
+    // With a single weight there's nothing to pick, and the RNG is left alone, so that games with
+    // a single kind of food play as they did before the kinds existed.
+    if count < 2 {
+        return 0;
+    }
+    let total: f64 = weights.clone().sum();
+    let mut pick = rng.gen_range(0_f64, total);
+    for (index, weight) in weights.enumerate() {
+        if pick < weight {
+            return index;
+        }
+        pick -= weight;
+    }
+    // Rounding errors can leave the pick just past the last weight.
+    count - 1
+}
@@ -0,0 +1,192 @@
+//! Plain-text level maps.
+//!
+//! Each line is a row of the board, and each character a cell:
+//!
+//! - `#`: wall;
+//! - `.`: floor;
+//! - `S`: start, that is, the tail end of the snake;
+//! - `^`, `>`, `v`, `<`: head of the snake, pointing to its direction.
+//!
+//! The head must be on the same row or column of the start, and point away from it; the cells in
+//! between are the snake body, so they must be floor. For example, `#S..>.#` describes a snake of
+//! length 3, moving right.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::config::MAX_BOARD_SIDE;
+use crate::{Cell, Vector};
+
+#[derive(Debug, PartialEq)]
+pub enum LevelErrorKind {
+    Empty,
+    TooLarge,
+    UnevenRow { expected: usize, found: usize },
+    UnknownCharacter(char),
+    DuplicateStart,
+    DuplicateHead,
+    MissingStart,
+    MissingHead,
+    MisalignedHead,
+    BlockedSnake,
+}
+
+/// Error found while parsing a level; lines and columns start from 1.
+#[derive(Debug, PartialEq)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: LevelErrorKind,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            LevelErrorKind::Empty => write!(f, "the level is empty"),
+            LevelErrorKind::TooLarge => write!(
+                f,
+                "the level must be at most {}x{} cells",
+                MAX_BOARD_SIDE, MAX_BOARD_SIDE
+            ),
+            LevelErrorKind::UnevenRow { expected, found } => write!(
+                f,
+                "the row has {} cells, but the first one has {}",
+                found, expected
+            ),
+            LevelErrorKind::UnknownCharacter(character) => {
+                write!(f, "unknown character {:?}", character)
+            }
+            LevelErrorKind::DuplicateStart => write!(f, "the start is already defined"),
+            LevelErrorKind::DuplicateHead => write!(f, "the head is already defined"),
+            LevelErrorKind::MissingStart => write!(f, "the start (`S`) is missing"),
+            LevelErrorKind::MissingHead => write!(f, "the head (`^`, `>`, `v` or `<`) is missing"),
+            LevelErrorKind::MisalignedHead => {
+                write!(
+                    f,
+                    "the head must be in line with the start, and point away from it"
+                )
+            }
+            LevelErrorKind::BlockedSnake => write!(f, "the snake body must lie on the floor"),
+        }
+    }
+}
+
+pub struct Level {
+    pub width: i32,
+    pub height: i32,
+    pub walls: BTreeSet<Cell>,
+    pub start: Cell,
+    pub head: Cell,
+    pub direction: Vector,
+}
+
+impl Level {
+    pub fn parse(source: &str) -> Result<Level, LevelError> {
+        let rows: Vec<&str> = source.trim_end().lines().map(str::trim_end).collect();
+        let error = |line: usize, column: usize, kind| {
+            Err(LevelError {
+                line: line + 1,
+                column: column + 1,
+                kind,
+            })
+        };
+
+        if rows.is_empty() {
+            return error(0, 0, LevelErrorKind::Empty);
+        }
+        let width = rows[0].chars().count();
+        let max_side = MAX_BOARD_SIDE as usize;
+        if width > max_side {
+            return error(0, max_side, LevelErrorKind::TooLarge);
+        }
+        if rows.len() > max_side {
+            return error(max_side, 0, LevelErrorKind::TooLarge);
+        }
+
+        let mut walls = BTreeSet::new();
+        let mut start = None;
+        let mut head: Option<(Cell, Vector)> = None;
+        for (y, row) in rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != width {
+                return error(
+                    y,
+                    found.min(width),
+                    LevelErrorKind::UnevenRow {
+                        expected: width,
+                        found,
+                    },
+                );
+            }
+            for (x, character) in row.chars().enumerate() {
+                let cell = (x as i32, y as i32);
+                let direction = match character {
+                    '#' => {
+                        walls.insert(cell);
+                        continue;
+                    }
+                    '.' => continue,
+                    'S' => {
+                        if start.is_some() {
+                            return error(y, x, LevelErrorKind::DuplicateStart);
+                        }
+                        start = Some(cell);
+                        continue;
+                    }
+                    '^' => Vector::new(0_f64, -1_f64),
+                    '>' => Vector::new(1_f64, 0_f64),
+                    'v' => Vector::new(0_f64, 1_f64),
+                    '<' => Vector::new(-1_f64, 0_f64),
+                    _ => return error(y, x, LevelErrorKind::UnknownCharacter(character)),
+                };
+                if head.is_some() {
+                    return error(y, x, LevelErrorKind::DuplicateHead);
+                }
+                head = Some((cell, direction));
+            }
+        }
+
+        let end = rows.len();
+        let start = match start {
+            Some(start) => start,
+            None => return error(end, 0, LevelErrorKind::MissingStart),
+        };
+        let (head, direction) = match head {
+            Some(head) => head,
+            None => return error(end, 0, LevelErrorKind::MissingHead),
+        };
+        let head_error = |kind| error(head.1 as usize, head.0 as usize, kind);
+
+        let (delta_x, delta_y) = (head.0 - start.0, head.1 - start.1);
+        let length = delta_x.abs() + delta_y.abs();
+        let aligned = (delta_x == 0 || delta_y == 0)
+            && length > 0
+            && delta_x.signum() == direction.x as i32
+            && delta_y.signum() == direction.y as i32;
+        if !aligned {
+            return head_error(LevelErrorKind::MisalignedHead);
+        }
+        if (1..length).any(|step| {
+            walls.contains(&(
+                start.0 + step * direction.x as i32,
+                start.1 + step * direction.y as i32,
+            ))
+        }) {
+            return head_error(LevelErrorKind::BlockedSnake);
+        }
+
+        Ok(Level {
+            width: width as i32,
+            height: rows.len() as i32,
+            walls,
+            start,
+            head,
+            direction,
+        })
+    }
+
+    pub fn snake_length(&self) -> i32 {
+        (self.head.0 - self.start.0).abs() + (self.head.1 - self.start.1).abs()
+    }
+}
@@ -1,15 +1,53 @@
-use js_sys::Array;
-use rand::Rng;
+use std::collections::{BTreeSet, VecDeque};
+
+use js_sys::{Array, Float64Array};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+pub mod bot;
+pub mod config;
+pub mod difficulty;
+pub mod env;
+pub mod events;
+pub mod food;
+pub mod level;
+mod occupancy;
+pub mod power_up;
+pub mod replay;
+pub mod save;
+pub mod session;
+pub mod snake;
+pub mod topology;
+
+use config::{ConfigError, GameConfig};
+use difficulty::SpeedCurve;
+use events::{DeathCause, DeathCauseKind, GameEvent};
+use food::{Bonus, Food, FoodRules};
+use level::Level;
+use occupancy::{Occupancy, Track};
+use power_up::{ActiveEffect, EffectStatus, PowerUp, PowerUpRules};
+use snake::{Snake, SnakeBody};
+use topology::{Bounds, Topology};
+
 static EPSILON: f64 = 0.0000001;
 
+/// Default maximum number of turns each snake can have queued.
+pub const DEFAULT_TURN_QUEUE_DEPTH: usize = 3;
+
+/// Length of the frames run by `Game::advance()`, in milliseconds.
+pub const DEFAULT_FIXED_STEP: f64 = 10_f64;
+
+/// Board cell, as `(x, y)`; the cell `(x, y)` has its center in `(x + 0.5, y + 0.5)`.
+pub type Cell = (i32, i32);
+
 fn are_equal(one: f64, another: f64) -> bool {
     (one - another).abs() < EPSILON
 }
 
 #[wasm_bindgen]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Vector {
     pub x: f64,
     pub y: f64,
@@ -67,7 +105,7 @@ impl<'a> Segment<'a> {
     }
 
     pub fn get_vector(&self) -> Vector {
-        self.end.subtract(&self.start)
+        self.end.subtract(self.start)
     }
 
     pub fn length(&self) -> f64 {
@@ -82,36 +120,53 @@ impl<'a> Segment<'a> {
 
     pub fn get_projected_point(&self, point: &Vector) -> Vector {
         let vector = self.get_vector();
-        let diff = point.subtract(&self.start);
+        let diff = point.subtract(self.start);
         let u = diff.dot_product(&vector) / vector.dot_product(&vector);
         let scaled = vector.scale_by(u);
         self.start.add(&scaled)
     }
 }
 
-fn get_segments_from_vectors(vectors: &[Vector]) -> Vec<Segment> {
-    let pairs = vectors[..vectors.len() - 1].iter().zip(&vectors[1..]);
-    pairs
-        .map(|(s, e)| Segment::new(s, e))
-        .collect::<Vec<Segment>>()
+/// Writes the points as interleaved x and y coordinates, for the `*_buffer()` views.
+fn fill_points_buffer(points: &[Vector], buffer: &mut Vec<f64>) {
+    buffer.clear();
+    for point in points {
+        buffer.extend_from_slice(&[point.x, point.y]);
+    }
 }
 
-fn get_food(width: i32, height: i32, snake: &[Vector]) -> Vector {
-    let segments = get_segments_from_vectors(snake);
-    let mut free_positions: Vec<Vector> = Vec::new();
-    for x in 0..width {
-        for y in 0..height {
-            let point = Vector::new(f64::from(x) + 0.5, f64::from(y) + 0.5);
-            if segments.iter().all(|s| !s.is_point_inside(&point)) {
-                free_positions.push(point)
-            }
+/// Writes the polyline as `fill_points_buffer()` does, split as in `Bounds::pieces()` (without
+/// allocating), with a pair of NaNs between a piece and the next.
+fn fill_pieces_buffer(bounds: &Bounds, points: &[Vector], buffer: &mut Vec<f64>) {
+    buffer.clear();
+    let mut last: Option<Vector> = None;
+    for (start, end) in bounds.segments(points) {
+        match last {
+            Some(last) if last.equal_to(&start) => {}
+            Some(_) => buffer.extend_from_slice(&[f64::NAN, f64::NAN, start.x, start.y]),
+            None => buffer.extend_from_slice(&[start.x, start.y]),
         }
+        buffer.extend_from_slice(&[end.x, end.y]);
+        last = Some(end);
+    }
+}
+
+fn cell_of(point: &Vector) -> Cell {
+    (point.x.floor() as i32, point.y.floor() as i32)
+}
+
+/// A random free cell center, if there's any left.
+fn get_food(occupancy: &Occupancy, rng: &mut Pcg32) -> Option<Vector> {
+    if occupancy.free_count() == 0 {
+        return None;
     }
-    let index = rand::thread_rng().gen_range(0, free_positions.len());
-    free_positions[index]
+    let index = occupancy.nth_free(rng.gen_range(0, occupancy.free_count()));
+    let (x, y) = occupancy.cell(index);
+    Some(Vector::new(f64::from(x) + 0.5, f64::from(y) + 0.5))
 }
 
 #[wasm_bindgen]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum Movement {
     TOP,
     RIGHT,
@@ -119,161 +174,1298 @@ pub enum Movement {
     LEFT,
 }
 
+impl Movement {
+    pub fn direction(self) -> Vector {
+        match self {
+            Movement::TOP => Vector {
+                x: 0_f64,
+                y: -1_f64,
+            },
+            Movement::RIGHT => Vector { x: 1_f64, y: 0_f64 },
+            Movement::DOWN => Vector { x: 0_f64, y: 1_f64 },
+            Movement::LEFT => Vector {
+                x: -1_f64,
+                y: 0_f64,
+            },
+        }
+    }
+}
+
+/// Whether the game is still going on, and how it ended.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameStatus {
+    Running,
+    /// The snakes filled the board, leaving no room for the food.
+    Won,
+    /// The snakes died; the cause is the one of the death that ended the game.
+    Lost(DeathCause),
+}
+
+/// `GameStatus` without the loss cause, which is available via `Game::loss_cause()`.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    Running,
+    Won,
+    Lost,
+}
+
+/// Why `Game::enqueue_turn()` rejected a turn.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TurnRejection {
+    /// The queue already holds `turn_queue_depth` turns.
+    QueueFull,
+    /// The snake would already be moving in that direction.
+    Redundant,
+    /// The snake would have to reverse onto itself.
+    Opposite,
+}
+
 #[wasm_bindgen]
 pub struct Game {
     pub width: i32,
     pub height: i32,
+    /// The speed at the start of the game; see `current_speed()`.
     pub speed: f64,
-    snake: Vec<Vector>,
-    pub direction: Vector,
-    pub food: Vector,
-    pub score: i32,
+    /// If missing, the speed doesn't change with the score or the time.
+    speed_curve: Option<SpeedCurve>,
+    snakes: Vec<Snake>,
+    /// Movements requested via `steer()`, applied on the next `process()`.
+    movements: Vec<Option<Movement>>,
+    /// Turns requested via `enqueue_turn()`, applied one per cell center.
+    turns: Vec<VecDeque<Movement>>,
+    pub turn_queue_depth: usize,
+    /// Fewer items than the rules ask for once the board is almost full, and none once it's full;
+    /// see `refill_foods()`.
+    foods: Vec<Food>,
+    food_rules: FoodRules,
+    bonus: Option<Bonus>,
+    /// Game time at which the next bonus appears, if there's none on the board.
+    next_bonus: f64,
+    /// If missing, there are no power-ups.
+    power_up_rules: Option<PowerUpRules>,
+    power_up: Option<PowerUp>,
+    /// Game time at which the next power-up appears, if there's none on the board.
+    next_power_up: f64,
+    /// Effects of the collected power-ups, in the order they've been collected.
+    effects: Vec<ActiveEffect>,
+    /// Milliseconds processed since the start of the game.
+    clock: f64,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    seed: u64,
+    rng: Pcg32,
+    /// Derived from the snakes and the walls, so it isn't saved.
+    occupancy: Occupancy,
+    tracks: Vec<Track>,
+    status: GameStatus,
+    /// Events of the last `process()` call, until drained.
+    events: Vec<GameEvent>,
+    /// Length of the frames run by `advance()`.
+    pub fixed_step: f64,
+    /// Time passed to `advance()` that isn't enough for a frame yet.
+    accumulator: f64,
+    /// Backing memory of the `*_buffer()` views, reused across calls.
+    snake_buffer: Vec<f64>,
+    food_buffer: Vec<f64>,
+    /// Scratch copy of a snake body, for the interpolated views.
+    interpolated: SnakeBody,
 }
 
 #[wasm_bindgen]
 impl Game {
+    /// Fails with a readable message if the arguments are invalid; see `GameConfig`.
     #[wasm_bindgen(constructor)]
-    pub fn new(width: i32, height: i32, speed: f64, snake_length: i32, direction: Vector) -> Game {
-        let head_x = (f64::from(width) / 2_f64).round() - 0.5;
-        let head_y = (f64::from(height) / 2_f64).round() - 0.5;
-        let head = Vector::new(head_x, head_y);
-        let tailtip = head.subtract(&direction.scale_by(f64::from(snake_length)));
-        let snake = vec![tailtip, head];
-        let food = get_food(width, height, &snake);
+    pub fn new(
+        width: i32,
+        height: i32,
+        speed: f64,
+        snake_length: i32,
+        direction: Vector,
+    ) -> Result<Game, JsValue> {
+        let config = GameConfig::new(width, height)
+            .speed(speed)
+            .snake_length(snake_length)
+            .direction(direction);
+        Game::from_config(&config)
+    }
 
-        Game {
-            width: width,
-            height: height,
-            speed: speed,
-            snake: snake,
-            direction: direction,
-            food: food,
-            score: 0,
-        }
+    /// Same as the constructor, but all the random decisions (e.g. the food placement) are derived
+    /// from the given seed, so that the same inputs always produce the same game.
+    pub fn with_seed(
+        width: i32,
+        height: i32,
+        speed: f64,
+        snake_length: i32,
+        direction: Vector,
+        seed: u64,
+    ) -> Result<Game, JsValue> {
+        Game::with_players(width, height, speed, snake_length, direction, 1, seed)
+    }
+
+    /// Game with multiple snakes, all moving in the same direction, and spread evenly across the
+    /// board on the perpendicular axis.
+    pub fn with_players(
+        width: i32,
+        height: i32,
+        speed: f64,
+        snake_length: i32,
+        direction: Vector,
+        players: usize,
+        seed: u64,
+    ) -> Result<Game, JsValue> {
+        let config = GameConfig::new(width, height)
+            .speed(speed)
+            .snake_length(snake_length)
+            .direction(direction)
+            .players(players)
+            .seed(seed);
+        Game::from_config(&config)
+    }
+
+    pub fn from_config(config: &GameConfig) -> Result<Game, JsValue> {
+        Game::with_config(config).map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Creates a game from a level map (see the `level` module for the format).
+    pub fn from_level(level: &str, speed: f64) -> Result<Game, JsValue> {
+        let level = Level::parse(level).map_err(|error| JsValue::from_str(&error.to_string()))?;
+        Ok(Game::with_level(&level, speed, rand::thread_rng().gen()))
+    }
+
+    /// The seed the game RNG has been initialized with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Serializes the whole game state, including the RNG state, so that it can be restored exactly.
+    pub fn to_json(&self) -> String {
+        save::encode_json(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Game, JsValue> {
+        save::decode_json(json).map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Compact binary equivalent of `to_json()`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        save::encode_binary(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Game, JsValue> {
+        save::decode_binary(bytes).map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Direction of the first snake.
+    #[wasm_bindgen(getter)]
+    pub fn direction(&self) -> Vector {
+        self.snakes[0].direction
+    }
+
+    /// Score of the first snake.
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> i32 {
+        self.snakes[0].score
+    }
+
+    /// Fixed when the game is created, since the occupancy of the board depends on it.
+    #[wasm_bindgen(getter)]
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn players(&self) -> usize {
+        self.snakes.len()
+    }
+
+    pub fn get_player_score(&self, player: usize) -> i32 {
+        self.snakes[player].score
+    }
+
+    pub fn is_wall(&self, x: i32, y: i32) -> bool {
+        self.walls.contains(&(x, y))
+    }
+
+    /// The centers of the wall cells.
+    pub fn get_walls(&self) -> Array {
+        self.walls
+            .iter()
+            .map(|&(x, y)| JsValue::from(Vector::new(f64::from(x) + 0.5, f64::from(y) + 0.5)))
+            .collect()
+    }
+
+    /// The food items, in the order they spawned.
+    pub fn get_foods(&self) -> Array {
+        self.foods.iter().map(|&food| JsValue::from(food)).collect()
+    }
+
+    /// The bonus item, if there's one on the board.
+    pub fn bonus(&self) -> Option<Bonus> {
+        self.bonus
+    }
+
+    /// What the bonus would be worth if eaten now.
+    pub fn bonus_score(&self) -> Option<i32> {
+        let rules = self.food_rules.bonus?;
+        self.bonus.map(|bonus| rules.score(bonus.remaining))
+    }
+
+    /// The power-up, if there's one on the board.
+    pub fn power_up(&self) -> Option<PowerUp> {
+        self.power_up
+    }
+
+    /// The effects of the collected power-ups, as an array of objects with the effect `name`, the
+    /// `player` who collected it, the `remaining` milliseconds, and the number of `stacks`.
+    pub fn get_active_effects(&self) -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(&self.effect_statuses())
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        js_sys::JSON::parse(&json)
+    }
+
+    /// The speed, as changed by the speed curve and the active effects.
+    pub fn current_speed(&self) -> f64 {
+        let speed = match &self.speed_curve {
+            Some(curve) => curve.speed_at(self.speed, self.top_score(), self.clock),
+            None => self.speed,
+        };
+        let kinds = match &self.power_up_rules {
+            Some(rules) => rules.kinds(),
+            None => return speed,
+        };
+        self.effects.iter().fold(speed, |speed, effect| {
+            speed * kinds[effect.kind].effect().speed_factor(effect.stacks)
+        })
     }
 
+    pub fn is_alive(&self, player: usize) -> bool {
+        self.snakes[player].alive
+    }
+
+    /// A single-player game is over when the snake dies; a multiplayer one, when at most one snake
+    /// is left. Either is over when the board is full.
     pub fn is_over(&self) -> bool {
-        let snake_len = self.snake.len();
-        let head = self.snake[snake_len - 1];
-        let Vector {
-            x: head_x,
-            y: head_y,
-        } = head;
-
-        if head_x < 0.0 || head_x > self.width as f64 || head_y < 0.0 || head_y > self.height as f64
-        {
-            return true;
+        self.status != GameStatus::Running
+    }
+
+    pub fn status_kind(&self) -> StatusKind {
+        match self.status {
+            GameStatus::Running => StatusKind::Running,
+            GameStatus::Won => StatusKind::Won,
+            GameStatus::Lost(_) => StatusKind::Lost,
         }
+    }
+
+    /// The cause of the death that ended the game, if it's lost; see `loss_details()` for where it
+    /// happened.
+    pub fn loss_cause(&self) -> Option<DeathCauseKind> {
+        self.death_cause().map(|cause| cause.kind())
+    }
+
+    /// The cause of the death that ended the game, as an object (see `death_cause()`), or `null`
+    /// if the game isn't lost.
+    pub fn loss_details(&self) -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(&self.death_cause())
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        js_sys::JSON::parse(&json)
+    }
+
+    /// Sets the movement of the given snake, for the next `process()` call; it's discarded if the
+    /// snake doesn't reach a cell center during the call, or if it has queued turns.
+    pub fn steer(&mut self, player: usize, movement: Option<Movement>) {
+        self.movements[player] = movement;
+    }
+
+    /// Queues a turn of the given snake, to be applied at the first cell center after the turns
+    /// already queued; returns why the turn has been rejected, if it has.
+    pub fn enqueue_turn(&mut self, player: usize, movement: Movement) -> Option<TurnRejection> {
+        self.push_turn(player, movement).err()
+    }
 
-        if snake_len < 5 {
-            return false;
+    pub fn queued_turns(&self, player: usize) -> usize {
+        self.turns[player].len()
+    }
+
+    fn process_movement(&mut self, distance: f64, timespan: f64) {
+        let bounds = self.bounds();
+        let snakes = self
+            .snakes
+            .iter_mut()
+            .zip(self.movements.iter())
+            .zip(self.turns.iter_mut())
+            .zip(self.tracks.iter_mut());
+        for (player, (((snake, movement), turns), track)) in snakes.enumerate() {
+            let movement = *movement;
+            if !snake.alive {
+                continue;
+            }
+            let turn = turns.front().copied();
+            let tail_distance = snake.tail_advance(distance);
+            let turned = snake.advance(distance, turn.or(movement));
+            if turned.is_some() && turn.is_some() {
+                turns.pop_front();
+            }
+            if let Some((at, travelled)) = turned {
+                self.events.push(GameEvent::Turned {
+                    time: timespan * travelled / distance,
+                    player,
+                    at: at.add(&bounds.wrapping_offset(&at)),
+                    direction: snake.direction,
+                });
+            }
+            snake.wrap(&bounds);
+            track.advance(snake, tail_distance, &bounds, &mut self.occupancy);
         }
+    }
 
-        let segments = get_segments_from_vectors(&self.snake[..snake_len - 3]);
+    /// Kills the snakes that hit the board edges or a wall, themselves, or another snake. Snakes
+    /// whose heads collide die together; dead snakes are left out of the collisions.
+    fn process_collisions(&mut self, time: f64) {
+        // Empty in most frames, in which case collecting doesn't allocate.
+        let deaths: Vec<(usize, DeathCause)> = (0..self.snakes.len())
+            .filter(|&player| self.snakes[player].alive)
+            .filter_map(|player| self.collision_cause(player).map(|cause| (player, cause)))
+            .collect();
+        for &(player, cause) in &deaths {
+            self.snakes[player].alive = false;
+            self.tracks[player].release(&mut self.occupancy);
+            self.events.push(GameEvent::Died {
+                time,
+                player,
+                cause,
+            });
+        }
+        if let Some(&(_, cause)) = deaths.first() {
+            self.update_status(cause);
+        }
+    }
 
-        segments.iter().any(|segment| {
-            let projected = segment.get_projected_point(&head);
-            segment.is_point_inside(&projected) && Segment::new(&head, &projected).length() < 0.5
-        })
+    fn process_food(&mut self, distance: f64, timespan: f64) {
+        let bounds = self.bounds();
+        // The items spawned along the way are checked from the next stretch on.
+        let mut unchecked = self.foods.len();
+        let mut index = 0;
+        while unchecked > 0 {
+            unchecked -= 1;
+            let food = self.foods[index];
+            let eater = self.eater_of(&food.position, &bounds);
+
+            let time = if let Some(eater) = eater {
+                let kind = self.food_rules.kinds[food.kind];
+                let score_factor = self.score_factor(eater);
+                let snake = &mut self.snakes[eater];
+                for _ in 0..kind.growth {
+                    snake.grow();
+                }
+                snake.score += kind.score * score_factor;
+                // The head went past the food by the distance between them.
+                let time = if distance > 0_f64 {
+                    let overshoot = bounds.distance(&snake.head(), &food.position) / distance;
+                    (timespan * (1_f64 - overshoot)).max(0_f64)
+                } else {
+                    0_f64
+                };
+                self.events.push(GameEvent::FoodEaten {
+                    time,
+                    player: eater,
+                    position: food.position,
+                    kind: food.kind,
+                    new_score: snake.score,
+                });
+                time
+            } else {
+                match food.remaining {
+                    // The stretches are cut where items expire, give or take rounding errors.
+                    Some(remaining) if remaining <= timespan + EPSILON => {
+                        let time = remaining.clamp(0_f64, timespan);
+                        self.events.push(GameEvent::FoodExpired {
+                            time,
+                            position: food.position,
+                        });
+                        time
+                    }
+                    Some(remaining) => {
+                        self.foods[index].remaining = Some(remaining - timespan);
+                        index += 1;
+                        continue;
+                    }
+                    None => {
+                        index += 1;
+                        continue;
+                    }
+                }
+            };
+
+            self.foods.remove(index);
+            self.refill_foods(time);
+        }
     }
 
-    fn process_movement(&mut self, timespan: f64, movement: Option<Movement>) {
-        let distance = self.speed * timespan;
-        let mut tail: Vec<Vector> = Vec::new();
-        let mut snake_distance = distance;
-        while self.snake.len() > 1 {
-            let point = self.snake.remove(0);
-            let next = &self.snake[0];
-            let segment = Segment::new(&point, next);
-            let length = segment.length();
-            if length >= snake_distance {
-                let vector = segment.get_vector().normalize().scale_by(snake_distance);
-                tail.push(point.add(&vector));
-                break;
+    /// Spawns the missing food items, after a cell has been freed for them, at the given time of
+    /// the stretch; the game is won once there's neither food nor a free cell left. Cells held by
+    /// the bonus or the power-up don't count as taken, so the food spawns once they go away.
+    fn refill_foods(&mut self, time: f64) {
+        if self.is_over() || self.foods.len() >= self.food_rules.count {
+            return;
+        }
+        let first = self.foods.len();
+        self.spawn_foods();
+        for food in &self.foods[first..] {
+            self.events.push(GameEvent::FoodSpawned {
+                time,
+                position: food.position,
+                kind: food.kind,
+            });
+        }
+        if self.foods.is_empty() && self.occupancy.free_count() == 0 {
+            self.status = GameStatus::Won;
+            self.events.push(GameEvent::Won { time });
+        }
+    }
+
+    /// Eats, expires or spawns the bonus, if the rules have one; `self.clock` is the time at the
+    /// start of the stretch.
+    fn process_bonus(&mut self, distance: f64, timespan: f64) {
+        let rules = match self.food_rules.bonus {
+            Some(rules) => rules,
+            None => return,
+        };
+        let bounds = self.bounds();
+
+        if let Some(bonus) = self.bonus {
+            let time = if let Some(eater) = self.eater_of(&bonus.position, &bounds) {
+                let score_factor = self.score_factor(eater);
+                let snake = &mut self.snakes[eater];
+                for _ in 0..rules.growth {
+                    snake.grow();
+                }
+                let time = if distance > 0_f64 {
+                    let overshoot = bounds.distance(&snake.head(), &bonus.position) / distance;
+                    (timespan * (1_f64 - overshoot)).max(0_f64)
+                } else {
+                    0_f64
+                };
+                let score = rules.score(bonus.remaining - time) * score_factor;
+                snake.score += score;
+                self.events.push(GameEvent::BonusEaten {
+                    time,
+                    player: eater,
+                    position: bonus.position,
+                    score,
+                    new_score: snake.score,
+                });
+                time
+            } else if bonus.remaining <= timespan + EPSILON {
+                // The stretches are cut where the bonus expires, give or take rounding errors.
+                let time = bonus.remaining.clamp(0_f64, timespan);
+                self.events.push(GameEvent::BonusExpired {
+                    time,
+                    position: bonus.position,
+                });
+                time
             } else {
-                snake_distance -= length;
+                self.bonus = Some(Bonus {
+                    remaining: bonus.remaining - timespan,
+                    ..bonus
+                });
+                return;
+            };
+            self.bonus = None;
+            self.next_bonus = self.clock + time + rules.next_interval(&mut self.rng);
+            self.refill_foods(time);
+        }
+
+        let time = self.next_bonus - self.clock;
+        // Likewise, they're cut where it appears.
+        if self.bonus.is_none() && time <= timespan + EPSILON {
+            let time = time.clamp(0_f64, timespan);
+            self.take_food_cells(true);
+            let position = get_food(&self.occupancy, &mut self.rng);
+            self.take_food_cells(false);
+            match position {
+                Some(position) => {
+                    self.bonus = Some(Bonus {
+                        position,
+                        remaining: rules.lifetime - (timespan - time),
+                    });
+                    self.events.push(GameEvent::BonusSpawned { time, position });
+                }
+                // The board is full; the next one may find room.
+                None => {
+                    self.next_bonus = self.clock + timespan + rules.next_interval(&mut self.rng)
+                }
             }
         }
-        tail.append(&mut self.snake);
-        self.snake = tail;
-        let old_head = self.snake.pop().unwrap();
-        let new_head = old_head.add(&self.direction.scale_by(distance));
-        if movement.is_some() {
-            let new_direction = match movement.unwrap() {
-                Movement::TOP => Vector {
-                    x: 0_f64,
-                    y: -1_f64,
-                },
-                Movement::RIGHT => Vector { x: 1_f64, y: 0_f64 },
-                Movement::DOWN => Vector { x: 0_f64, y: 1_f64 },
-                Movement::LEFT => Vector {
-                    x: -1_f64,
-                    y: 0_f64,
-                },
+    }
+
+    /// Ends the effects that run out, and collects, expires or spawns the power-up, if the rules
+    /// have power-ups; `self.clock` is the time at the start of the stretch.
+    fn process_power_ups(&mut self, distance: f64, timespan: f64) {
+        // Taken out for the duration, so that the game can be changed while they're borrowed;
+        // nothing in between reads them.
+        if let Some(rules) = self.power_up_rules.take() {
+            self.process_power_ups_with(&rules, distance, timespan);
+            self.power_up_rules = Some(rules);
+        }
+    }
+
+    fn process_power_ups_with(&mut self, rules: &PowerUpRules, distance: f64, timespan: f64) {
+        let bounds = self.bounds();
+
+        let events = &mut self.events;
+        self.effects.retain_mut(|effect| {
+            // The stretches are cut where effects end, give or take rounding errors.
+            if effect.remaining <= timespan + EPSILON {
+                events.push(GameEvent::EffectEnded {
+                    time: effect.remaining.clamp(0_f64, timespan),
+                    player: effect.player,
+                    kind: effect.kind,
+                });
+                false
+            } else {
+                effect.remaining -= timespan;
+                true
+            }
+        });
+
+        if let Some(power_up) = self.power_up {
+            let time = if let Some(player) = self.eater_of(&power_up.position, &bounds) {
+                let kind = &rules.kinds()[power_up.kind];
+                let snake = &mut self.snakes[player];
+                let time = if distance > 0_f64 {
+                    let overshoot = bounds.distance(&snake.head(), &power_up.position) / distance;
+                    (timespan * (1_f64 - overshoot)).max(0_f64)
+                } else {
+                    0_f64
+                };
+                let shrunk = snake.shrink(kind.effect().shrink());
+                if shrunk > 0_f64 {
+                    self.tracks[player].advance(snake, shrunk, &bounds, &mut self.occupancy);
+                }
+                if kind.duration > 0_f64 {
+                    let remaining = kind.duration - (timespan - time);
+                    power_up::activate(
+                        &mut self.effects,
+                        rules.kinds(),
+                        power_up.kind,
+                        player,
+                        remaining,
+                    );
+                }
+                self.events.push(GameEvent::PowerUpCollected {
+                    time,
+                    player,
+                    position: power_up.position,
+                    kind: power_up.kind,
+                });
+                time
+            } else if power_up.remaining <= timespan + EPSILON {
+                let time = power_up.remaining.clamp(0_f64, timespan);
+                self.events.push(GameEvent::PowerUpExpired {
+                    time,
+                    position: power_up.position,
+                });
+                time
+            } else {
+                self.power_up = Some(PowerUp {
+                    remaining: power_up.remaining - timespan,
+                    ..power_up
+                });
+                return;
             };
-            if !self.direction.is_opposite(&new_direction)
-                && !self.direction.equal_to(&new_direction)
-            {
-                let Vector { x: old_x, y: old_y } = old_head;
-                let old_x_rounded = old_x.round();
-                let old_y_rounded = old_y.round();
-                let new_x_rounded = new_head.x.round();
-                let new_y_rounded = new_head.y.round();
-
-                let rounded_x_changed = !are_equal(old_x_rounded, new_x_rounded);
-                let rounded_y_changed = !are_equal(old_y_rounded, new_y_rounded);
-                if rounded_x_changed || rounded_y_changed {
-                    let (old, old_rounded, new_rounded) = if rounded_x_changed {
-                        (old_x, old_x_rounded, new_x_rounded)
-                    } else {
-                        (old_y, old_y_rounded, new_y_rounded)
-                    };
-                    let breakpoint_component = old_rounded
-                        + (if new_rounded > old_rounded {
-                            0.5_f64
-                        } else {
-                            -0.5_f64
-                        });
-                    let breakpoint = if rounded_x_changed {
-                        Vector::new(breakpoint_component, old_y)
-                    } else {
-                        Vector::new(old_x, breakpoint_component)
-                    };
-                    let vector =
-                        new_direction.scale_by(distance - (old - breakpoint_component).abs());
-                    let head = breakpoint.add(&vector);
-
-                    self.snake.push(breakpoint);
-                    self.snake.push(head);
-                    self.direction = new_direction;
-                    return;
+            self.power_up = None;
+            self.next_power_up = self.clock + time + rules.next_interval(&mut self.rng);
+            self.refill_foods(time);
+        }
+
+        let time = self.next_power_up - self.clock;
+        if self.power_up.is_none() && time <= timespan + EPSILON {
+            let time = time.clamp(0_f64, timespan);
+            self.take_food_cells(true);
+            let position = get_food(&self.occupancy, &mut self.rng);
+            self.take_food_cells(false);
+            match position {
+                Some(position) => {
+                    let mut power_up = rules.create_power_up(position, &mut self.rng);
+                    power_up.remaining -= timespan - time;
+                    self.power_up = Some(power_up);
+                    self.events.push(GameEvent::PowerUpSpawned {
+                        time,
+                        position,
+                        kind: power_up.kind,
+                    });
+                }
+                // The board is full; the next one may find room.
+                None => {
+                    self.next_power_up = self.clock + timespan + rules.next_interval(&mut self.rng)
                 }
             }
         }
-        self.snake.push(new_head);
     }
 
-    fn process_food(&mut self) {
-        let snake_len = self.snake.len();
-        let head_segment = Segment::new(&self.snake[snake_len - 2], &self.snake[snake_len - 1]);
+    /// Product of the score factors of the effects active for the given snake.
+    fn score_factor(&self, player: usize) -> i32 {
+        let kinds = match &self.power_up_rules {
+            Some(rules) => rules.kinds(),
+            None => return 1,
+        };
+        self.effects
+            .iter()
+            .filter(|effect| effect.player == player)
+            .map(|effect| kinds[effect.kind].effect().score_factor(effect.stacks))
+            .product()
+    }
 
-        if head_segment.is_point_inside(&self.food) {
-            let tail_end = &self.snake[0];
-            let before_tail_end = &self.snake[1];
-            let tail_segment = Segment::new(before_tail_end, &tail_end);
-            let new_tail_end = tail_end.add(&tail_segment.get_vector().normalize());
-            self.snake[0] = new_tail_end;
-            self.score += 1;
-            self.food = get_food(self.width, self.height, &self.snake);
-        }
+    /// Whether the given snake has an effect active letting it go through its own body.
+    fn is_ghost(&self, player: usize) -> bool {
+        let kinds = match &self.power_up_rules {
+            Some(rules) => rules.kinds(),
+            None => return false,
+        };
+        self.effects
+            .iter()
+            .any(|effect| effect.player == player && kinds[effect.kind].effect().is_ghost())
+    }
+
+    /// Milliseconds until the next change that happens at a given time, rather than where the
+    /// heads are: the first active effect ending, the speed curve stepping up, a food item
+    /// expiring, or the bonus or the power-up appearing or expiring.
+    fn next_timed_change(&self) -> Option<f64> {
+        let step = self
+            .speed_curve
+            .and_then(|curve| curve.next_step(self.speed, self.clock));
+        let bonus = match (self.food_rules.bonus, self.bonus) {
+            (_, Some(bonus)) => Some(bonus.remaining),
+            (Some(_), None) => Some(self.next_bonus - self.clock),
+            (None, None) => None,
+        };
+        let foods = self.foods.iter().filter_map(|food| food.remaining);
+        let power_up = match (&self.power_up_rules, self.power_up) {
+            (_, Some(power_up)) => Some(power_up.remaining),
+            (Some(_), None) => Some(self.next_power_up - self.clock),
+            (None, None) => None,
+        };
+        self.effects
+            .iter()
+            .map(|effect| effect.remaining)
+            .chain(step)
+            .chain(foods)
+            .chain(bonus)
+            .chain(power_up)
+            .reduce(f64::min)
+    }
+
+    /// The highest score among the snakes, which drives the speed curves based on the score.
+    fn top_score(&self) -> i32 {
+        self.snakes
+            .iter()
+            .map(|snake| snake.score)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The alive snake whose head segment goes through the given point, if any.
+    fn eater_of(&self, point: &Vector, bounds: &Bounds) -> Option<usize> {
+        self.snakes.iter().position(|snake| {
+            snake.alive
+                && snake
+                    .head_segments(bounds)
+                    .any(|(start, end)| Segment::new(&start, &end).is_point_inside(point))
+        })
     }
 
+    /// Processes a frame; `movement` applies to the first snake, in addition to the `steer()` ones.
+    ///
+    /// The snakes are moved along their whole path through the frame, so that nothing is skipped
+    /// however long it is: the outcome is the same as with shorter frames adding up to it.
+    ///
+    /// The events of the frame replace the previous ones, and can be retrieved via `take_events()`.
+    /// Once the game is over, this does nothing; neither do frames that aren't a positive, finite
+    /// time, which couldn't be swept through.
     pub fn process(&mut self, timespan: f64, movement: Option<Movement>) {
-        self.process_movement(timespan, movement);
-        self.process_food();
+        self.events.clear();
+        if self.is_over() {
+            return;
+        }
+        if movement.is_some() {
+            self.movements[0] = movement;
+        }
+        if timespan.is_finite() && timespan > 0_f64 {
+            self.process_step(timespan);
+        }
+        self.end_frame();
+    }
+
+    /// Fixed-step alternative to `process()`: runs as many `fixed_step`-long frames as the elapsed
+    /// time allows, keeping the rest for the next call, so that no frame is long enough for the
+    /// snakes to skip over anything. `movement`, and the `steer()` ones, apply to all the frames.
+    ///
+    /// Returns how many frames have been run; their events replace the previous ones, timed from
+    /// the start of the first frame.
+    pub fn advance(&mut self, elapsed: f64, movement: Option<Movement>) -> u32 {
+        let steps = self.take_steps(elapsed);
+        self.run_steps(steps, movement);
+        steps
+    }
+
+    /// How far the time kept by `advance()` is into the next frame, between 0 and 1; it's the
+    /// `alpha` of the interpolated views.
+    pub fn step_alpha(&self) -> f64 {
+        (self.accumulator / self.fixed_step).clamp(0_f64, 1_f64)
+    }
+
+    /// Like `get_snake_buffer()`, for rendering between fixed-step frames: the snake is moved back
+    /// to where it was `alpha` of the way through the last frame. Since the last frame is always
+    /// complete, this trails the game time by less than a frame, but moves smoothly.
+    pub fn interpolated_snake(&mut self, alpha: f64) -> Float64Array {
+        self.interpolate(0, alpha);
+        fill_points_buffer(self.interpolated.points(), &mut self.snake_buffer);
+        unsafe { Float64Array::view(&self.snake_buffer) }
+    }
+
+    /// Like `get_player_snake_pieces_buffer()`, interpolated as in `interpolated_snake()`.
+    pub fn interpolated_player_snake_pieces(&mut self, player: usize, alpha: f64) -> Float64Array {
+        self.interpolate(player, alpha);
+        fill_pieces_buffer(
+            &self.bounds(),
+            self.interpolated.points(),
+            &mut self.snake_buffer,
+        );
+        unsafe { Float64Array::view(&self.snake_buffer) }
+    }
+
+    /// Drains the events of the last frame, as an array of objects tagged by their `type`.
+    pub fn take_events(&mut self) -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(&self.drain_events())
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        js_sys::JSON::parse(&json)
     }
 
     pub fn get_snake(&self) -> Array {
-        self.snake.clone().into_iter().map(JsValue::from).collect()
+        self.get_player_snake(0)
+    }
+
+    pub fn get_player_snake(&self, player: usize) -> Array {
+        self.snakes[player]
+            .body()
+            .iter()
+            .copied()
+            .map(JsValue::from)
+            .collect()
+    }
+
+    /// The snake as an array of polylines lying on the board; on toroidal boards, it's split
+    /// wherever it crosses an edge.
+    pub fn get_player_snake_pieces(&self, player: usize) -> Array {
+        self.bounds()
+            .pieces(self.snakes[player].body())
+            .into_iter()
+            .map(|piece| JsValue::from(piece.into_iter().map(JsValue::from).collect::<Array>()))
+            .collect()
+    }
+
+    /// Like `get_snake()`, as interleaved x and y coordinates.
+    ///
+    /// The array is a view into the wasm memory, so that no JS objects are created per frame. It's
+    /// valid only until the next call to a snake buffer method, or until the wasm memory grows, so
+    /// it must be read (or copied) right away.
+    pub fn get_snake_buffer(&mut self) -> Float64Array {
+        self.get_player_snake_buffer(0)
+    }
+
+    pub fn get_player_snake_buffer(&mut self, player: usize) -> Float64Array {
+        self.fill_snake_buffer(player);
+        // Safe as long as the caller follows the rules above.
+        unsafe { Float64Array::view(&self.snake_buffer) }
+    }
+
+    /// Like `get_player_snake_pieces()`, as interleaved x and y coordinates, with a pair of NaNs
+    /// between a piece and the next; the same rules as `get_snake_buffer()` apply.
+    pub fn get_player_snake_pieces_buffer(&mut self, player: usize) -> Float64Array {
+        self.fill_snake_pieces_buffer(player);
+        unsafe { Float64Array::view(&self.snake_buffer) }
+    }
+
+    /// The food positions as interleaved x and y coordinates, in the order of `get_foods()`; it's
+    /// empty once the board is full. The view is valid only until the next call, or until the wasm
+    /// memory grows.
+    pub fn get_food_buffer(&mut self) -> Float64Array {
+        self.fill_food_buffer();
+        unsafe { Float64Array::view(&self.food_buffer) }
+    }
+}
+
+impl Game {
+    fn create(
+        bounds: Bounds,
+        speed: f64,
+        snakes: Vec<Snake>,
+        walls: BTreeSet<Cell>,
+        food_rules: FoodRules,
+        seed: u64,
+    ) -> Game {
+        let rng = Pcg32::seed_from_u64(seed);
+        let (occupancy, tracks) = occupancy::track_snakes(&bounds, &snakes, &walls);
+
+        let mut game = Game {
+            width: bounds.width,
+            height: bounds.height,
+            speed,
+            speed_curve: None,
+            movements: vec![None; snakes.len()],
+            turns: vec![VecDeque::new(); snakes.len()],
+            turn_queue_depth: DEFAULT_TURN_QUEUE_DEPTH,
+            snakes,
+            foods: Vec::new(),
+            food_rules,
+            bonus: None,
+            next_bonus: 0_f64,
+            power_up_rules: None,
+            power_up: None,
+            next_power_up: 0_f64,
+            effects: Vec::new(),
+            clock: 0_f64,
+            topology: bounds.topology,
+            walls,
+            seed,
+            rng,
+            occupancy,
+            tracks,
+            status: GameStatus::Running,
+            events: Vec::new(),
+            fixed_step: DEFAULT_FIXED_STEP,
+            accumulator: 0_f64,
+            snake_buffer: Vec::new(),
+            food_buffer: Vec::new(),
+            interpolated: SnakeBody::default(),
+        };
+        game.spawn_foods();
+        if game.foods.is_empty() {
+            game.status = GameStatus::Won;
+        }
+        if let Some(bonus) = game.food_rules.bonus {
+            game.next_bonus = bonus.next_interval(&mut game.rng);
+        }
+        game
+    }
+
+    /// Spawns food items up to the configured count, as long as there are free cells.
+    fn spawn_foods(&mut self) {
+        let bounds = self.bounds();
+        self.take_food_cells(true);
+        while self.foods.len() < self.food_rules.count {
+            let position = match get_food(&self.occupancy, &mut self.rng) {
+                Some(position) => position,
+                None => break,
+            };
+            let food = self.food_rules.create_food(position, &mut self.rng);
+            if let Some(index) = self.occupancy.index(&bounds, cell_of(&position)) {
+                self.occupancy.occupy(index);
+            }
+            self.foods.push(food);
+        }
+        self.take_food_cells(false);
+    }
+
+    /// Marks the cells holding food (bonus and power-up included) as occupied, or frees them again, so that new
+    /// items are spawned elsewhere.
+    fn take_food_cells(&mut self, take: bool) {
+        let bounds = self.bounds();
+        let positions = self.foods.iter().map(|food| food.position);
+        let extras = self.bonus.map(|bonus| bonus.position).into_iter();
+        let extras = extras.chain(self.power_up.map(|power_up| power_up.position));
+        for position in positions.chain(extras) {
+            if let Some(index) = self.occupancy.index(&bounds, cell_of(&position)) {
+                if take {
+                    self.occupancy.occupy(index);
+                } else {
+                    self.occupancy.release(index);
+                }
+            }
+        }
+    }
+
+    fn fill_snake_buffer(&mut self, player: usize) {
+        fill_points_buffer(self.snakes[player].body(), &mut self.snake_buffer);
+    }
+
+    fn fill_snake_pieces_buffer(&mut self, player: usize) {
+        fill_pieces_buffer(
+            &self.bounds(),
+            self.snakes[player].body(),
+            &mut self.snake_buffer,
+        );
+    }
+
+    /// Copies the snake body to the interpolated one, moved back by the rest of the last frame; the
+    /// snakes don't move once the game is over, so they're left as they are.
+    fn interpolate(&mut self, player: usize, alpha: f64) {
+        let snake = &self.snakes[player];
+        self.interpolated.clone_from(&snake.body);
+        let rewind = (1_f64 - alpha.clamp(0_f64, 1_f64)) * self.current_speed() * self.fixed_step;
+        if snake.alive && !self.is_over() && rewind > 0_f64 {
+            // Approximately: the tail end may have stopped, or restarted, within the last frame.
+            let tail_rewind = if snake.growth > 0_f64 { 0_f64 } else { rewind };
+            self.interpolated.rewind(rewind, tail_rewind);
+        }
+    }
+
+    /// Adds to the time kept by `advance()`, returning how many fixed-step frames it's enough for;
+    /// their time is removed from it. Nothing runs unless the step is positive, and the elapsed time
+    /// is ignored unless it's positive and finite.
+    pub(crate) fn take_steps(&mut self, elapsed: f64) -> u32 {
+        if !(self.fixed_step.is_finite()
+            && self.fixed_step > 0_f64
+            && elapsed.is_finite()
+            && elapsed > 0_f64)
+        {
+            return 0;
+        }
+        self.accumulator += elapsed;
+        let steps = (self.accumulator / self.fixed_step).floor().max(0_f64);
+        self.accumulator -= steps * self.fixed_step;
+        steps as u32
+    }
+
+    /// Runs the given number of fixed-step frames, as a single `process()` call; once the game is
+    /// over, the rest are skipped.
+    pub(crate) fn run_steps(&mut self, steps: u32, movement: Option<Movement>) {
+        self.events.clear();
+        if movement.is_some() {
+            self.movements[0] = movement;
+        }
+        for step in 0..steps {
+            if self.is_over() {
+                break;
+            }
+            let first = self.events.len();
+            self.process_step(self.fixed_step);
+            let start = f64::from(step) * self.fixed_step;
+            for event in &mut self.events[first..] {
+                event.delay(start);
+            }
+        }
+        self.end_frame();
+    }
+
+    /// Moves the snakes through the frame, stopping whenever a head reaches a cell center or edge:
+    /// that's where snakes turn, eat, and collide, so that the outcome doesn't depend on how the
+    /// time is split into frames, and the events are timed exactly.
+    ///
+    /// Whenever the speed changes, the rest of the frame is processed at the new speed.
+    fn process_step(&mut self, timespan: f64) {
+        let mut elapsed = 0_f64;
+        while let Some(consumed) = self.process_pass(elapsed, timespan - elapsed) {
+            elapsed += consumed;
+            if self.is_over() {
+                break;
+            }
+        }
+    }
+
+    /// Processes the given part of the frame, starting `offset` into it, at the current speed;
+    /// stops early after the stretch in which the speed changed, if it did, returning the time
+    /// processed.
+    fn process_pass(&mut self, offset: f64, timespan: f64) -> Option<f64> {
+        let speed = self.current_speed();
+        let distance = speed * timespan;
+        let mut remaining = distance;
+        loop {
+            let mut stretch = self.next_stop().min(remaining);
+            // The stretch stops at the timed changes, so that they happen at the same point of
+            // the snakes' paths however the time is split into frames; effects ending and speed
+            // steps also change the speed.
+            if let Some(end) = self.next_timed_change() {
+                stretch = stretch.min(speed * end.max(0_f64));
+            }
+            let (start, stretch_timespan) = if distance > 0_f64 {
+                (
+                    timespan * (distance - remaining) / distance,
+                    timespan * stretch / distance,
+                )
+            } else {
+                (0_f64, timespan)
+            };
+            let first = self.events.len();
+            self.process_movement(stretch, stretch_timespan);
+            self.process_food(stretch, stretch_timespan);
+            self.process_bonus(stretch, stretch_timespan);
+            self.process_power_ups(stretch, stretch_timespan);
+            self.process_collisions(stretch_timespan);
+            self.clock += stretch_timespan;
+            for event in &mut self.events[first..] {
+                event.delay(offset + start);
+            }
+            remaining -= stretch;
+            if remaining <= 0_f64 || self.is_over() {
+                return None;
+            }
+            if self.current_speed() != speed {
+                return Some(start + stretch_timespan);
+            }
+        }
+    }
+
+    /// Distance until the first head (of the alive snakes) reaches a cell center or edge, which
+    /// are half a cell apart; heads within `EPSILON` of one are considered past it.
+    fn next_stop(&self) -> f64 {
+        self.snakes
+            .iter()
+            .filter(|snake| snake.alive)
+            .map(|snake| {
+                let head = snake.head();
+                let direction = snake.direction;
+                // The head position along its direction, in half cells.
+                let position = 2_f64 * (head.x * direction.x + head.y * direction.y);
+                ((position + EPSILON).floor() + 1_f64 - position) / 2_f64
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Discards the `steer()` movements, which last for a single `process()` call, and sorts the
+    /// events of the call.
+    fn end_frame(&mut self) {
+        for movement in &mut self.movements {
+            *movement = None;
+        }
+        self.events
+            .sort_by(|one, another| one.time().total_cmp(&another.time()));
+    }
+
+    /// The contents of the `get_food_buffer()` view.
+    fn fill_food_buffer(&mut self) {
+        self.food_buffer.clear();
+        for food in &self.foods {
+            self.food_buffer
+                .extend_from_slice(&[food.position.x, food.position.y]);
+        }
+    }
+
+    /// Native equivalent of `from_config()`.
+    pub fn with_config(config: &GameConfig) -> Result<Game, ConfigError> {
+        config.validate()?;
+        let snakes = config
+            .heads()
+            .into_iter()
+            .map(|head| Snake::new(head, config.direction, config.snake_length))
+            .collect();
+        let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let bounds = Bounds {
+            width: config.width,
+            height: config.height,
+            topology: config.topology,
+        };
+        let mut game = Game::create(
+            bounds,
+            config.speed,
+            snakes,
+            BTreeSet::new(),
+            config.food_rules(),
+            seed,
+        );
+        game.speed_curve = config.speed_curve;
+        game.turn_queue_depth = config.turn_queue_depth;
+        game.enable_power_ups(config.power_ups.clone());
+        Ok(game)
+    }
+
+    pub fn with_level(level: &Level, speed: f64, seed: u64) -> Game {
+        Game::create_from_level(level, speed, Topology::Bounded, FoodRules::default(), seed)
+    }
+
+    /// Game from a level map, with the settings of the config that the map doesn't override (the
+    /// speed and its curve, topology, turn queue depth, food and power-ups); the config is assumed
+    /// to be valid.
+    pub fn with_level_config(level: &Level, config: &GameConfig, seed: u64) -> Game {
+        let mut game = Game::create_from_level(
+            level,
+            config.speed,
+            config.topology,
+            config.food_rules(),
+            seed,
+        );
+        game.speed_curve = config.speed_curve;
+        game.turn_queue_depth = config.turn_queue_depth;
+        game.enable_power_ups(config.power_ups.clone());
+        game
+    }
+
+    /// Sets the power-up rules, scheduling the first power-up; the RNG is left alone without them.
+    fn enable_power_ups(&mut self, rules: Option<PowerUpRules>) {
+        if let Some(rules) = &rules {
+            self.next_power_up = rules.next_interval(&mut self.rng);
+        }
+        self.power_up_rules = rules;
+    }
+
+    fn create_from_level(
+        level: &Level,
+        speed: f64,
+        topology: Topology,
+        food_rules: FoodRules,
+        seed: u64,
+    ) -> Game {
+        let center = |(x, y): Cell| Vector::new(f64::from(x) + 0.5, f64::from(y) + 0.5);
+        let snake = Snake::new(center(level.head), level.direction, level.snake_length());
+        let bounds = Bounds {
+            width: level.width,
+            height: level.height,
+            topology,
+        };
+        Game::create(
+            bounds,
+            speed,
+            vec![snake],
+            level.walls.clone(),
+            food_rules,
+            seed,
+        )
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Why the given snake would die in its current position, if it would.
+    fn collision_cause(&self, player: usize) -> Option<DeathCause> {
+        let bounds = self.bounds();
+        let snake = &self.snakes[player];
+        if let Some(side) = snake.exit_side(&bounds) {
+            return Some(DeathCause::Wall { side });
+        }
+        if let Some(cell) = snake.wall_hit(&self.walls, &bounds) {
+            return Some(DeathCause::Obstacle { cell });
+        }
+        if !self.is_ghost(player) {
+            let track = &self.tracks[player];
+            if let Some(center) = track.bitten_center(snake, &bounds, &self.occupancy) {
+                return Some(DeathCause::SelfCollision {
+                    segment_index: snake.segment_at(center),
+                    point: snake.head(),
+                });
+            }
+        }
+        let head = snake.probe();
+        self.snakes
+            .iter()
+            .enumerate()
+            .filter(|&(other_player, other)| other_player != player && other.alive)
+            .find_map(|(_, other)| {
+                if bounds.distance(&head, &other.head()) < 1_f64 {
+                    Some(DeathCause::HeadToHead)
+                } else if other.is_hit_by(&head, &bounds) {
+                    Some(DeathCause::OtherSnake)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Ends the game if too few snakes are left, blaming the given death.
+    fn update_status(&mut self, cause: DeathCause) {
+        let alive = self.snakes.iter().filter(|snake| snake.alive).count();
+        if self.status == GameStatus::Running && alive < self.snakes.len().min(2) {
+            self.status = GameStatus::Lost(cause);
+        }
+    }
+
+    /// Native equivalent of `enqueue_turn()`.
+    pub fn push_turn(&mut self, player: usize, movement: Movement) -> Result<(), TurnRejection> {
+        let turns = &mut self.turns[player];
+        if turns.len() >= self.turn_queue_depth {
+            return Err(TurnRejection::QueueFull);
+        }
+        // Queued turns are always applicable, so the direction is the one of the last of them.
+        let current = match turns.back() {
+            Some(turn) => turn.direction(),
+            None => self.snakes[player].direction,
+        };
+        let direction = movement.direction();
+        if current.equal_to(&direction) {
+            Err(TurnRejection::Redundant)
+        } else if current.is_opposite(&direction) {
+            Err(TurnRejection::Opposite)
+        } else {
+            turns.push_back(movement);
+            Ok(())
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds {
+            width: self.width,
+            height: self.height,
+            topology: self.topology,
+        }
+    }
+
+    pub fn walls(&self) -> &BTreeSet<Cell> {
+        &self.walls
+    }
+
+    /// The food items, in the order they spawned.
+    pub fn foods(&self) -> &[Food] {
+        &self.foods
+    }
+
+    pub fn food_rules(&self) -> &FoodRules {
+        &self.food_rules
+    }
+
+    pub fn speed_curve(&self) -> Option<SpeedCurve> {
+        self.speed_curve
+    }
+
+    pub fn power_up_rules(&self) -> Option<&PowerUpRules> {
+        self.power_up_rules.as_ref()
+    }
+
+    /// The effects of the collected power-ups, in the order they've been collected.
+    pub fn active_effects(&self) -> &[ActiveEffect] {
+        &self.effects
+    }
+
+    fn effect_statuses(&self) -> Vec<EffectStatus> {
+        let kinds = match &self.power_up_rules {
+            Some(rules) => rules.kinds(),
+            None => return Vec::new(),
+        };
+        self.effects
+            .iter()
+            .map(|effect| EffectStatus {
+                name: kinds[effect.kind].effect().name(),
+                player: effect.player,
+                remaining: effect.remaining,
+                stacks: effect.stacks,
+            })
+            .collect()
+    }
+
+    /// The cause of the death that ended the game, if it's lost.
+    pub fn death_cause(&self) -> Option<DeathCause> {
+        match self.status {
+            GameStatus::Lost(cause) => Some(cause),
+            _ => None,
+        }
+    }
+
+    /// Returns the events of the last frame, in order of occurrence.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain(..).collect()
+    }
+
+    pub fn snakes(&self) -> &[Snake] {
+        &self.snakes
+    }
+
+    /// Body of the first snake.
+    pub fn snake(&self) -> &[Vector] {
+        self.snakes[0].body()
+    }
+
+    /// Head of the first snake.
+    pub fn head(&self) -> Vector {
+        self.snakes[0].head()
     }
 }
@@ -0,0 +1,324 @@
+//! Which board cells are covered by the snakes, kept up to date as they move.
+//!
+//! A snake covers a cell when the cell center lies on its body. Since the snakes only turn at cell
+//! centers, the centers along a body are exactly one unit apart, so the covered cells only change
+//! at the two ends: the head covers new cells as it advances, and the tail end leaves cells as it
+//! retracts.
+
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::snake::Snake;
+use crate::topology::{Bounds, Topology};
+use crate::{Cell, Vector, EPSILON};
+
+/// How many snakes (or walls) cover each cell, along with the set of the free ones.
+///
+/// Cells are indexed by column first, which is the order in which the food position is picked
+/// among the free cells.
+pub(crate) struct Occupancy {
+    width: i32,
+    height: i32,
+    counts: Vec<u16>,
+    /// Fenwick tree over the free cells, for finding the n-th one in logarithmic time.
+    free_tree: Vec<u32>,
+    free_count: usize,
+}
+
+impl Occupancy {
+    pub fn new(bounds: &Bounds, walls: &BTreeSet<Cell>) -> Occupancy {
+        let size = (bounds.width * bounds.height) as usize;
+        // All the cells are free, so each node covers as many cells as its lowest set bit.
+        let free_tree = (1..=size).map(|node| (node & node.wrapping_neg()) as u32);
+        let mut occupancy = Occupancy {
+            width: bounds.width,
+            height: bounds.height,
+            counts: vec![0; size],
+            free_tree: std::iter::once(0).chain(free_tree).collect(),
+            free_count: size,
+        };
+        for &wall in walls {
+            if let Some(index) = occupancy.index(bounds, wall) {
+                occupancy.occupy(index);
+            }
+        }
+        occupancy
+    }
+
+    /// The index of the cell, if it's on the board; on toroidal boards, cells are wrapped first.
+    pub fn index(&self, bounds: &Bounds, (x, y): Cell) -> Option<usize> {
+        let (x, y) = match bounds.topology {
+            Topology::Bounded => (x, y),
+            Topology::Toroidal => (x.rem_euclid(self.width), y.rem_euclid(self.height)),
+        };
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return None;
+        }
+        Some((x * self.height + y) as usize)
+    }
+
+    pub fn cell(&self, index: usize) -> Cell {
+        let index = index as i32;
+        (index / self.height, index % self.height)
+    }
+
+    pub fn free_count(&self) -> usize {
+        self.free_count
+    }
+
+    /// The index of the n-th free cell, in index order.
+    pub fn nth_free(&self, n: usize) -> usize {
+        let mut node = 0;
+        let mut remaining = n as u32;
+        let mut step = self.counts.len().next_power_of_two();
+        while step > 0 {
+            let next = node + step;
+            if next < self.free_tree.len() && self.free_tree[next] <= remaining {
+                node = next;
+                remaining -= self.free_tree[node];
+            }
+            step /= 2;
+        }
+        // The tree is 1-based, so the node following the prefix is the cell index plus one.
+        node
+    }
+
+    pub fn occupy(&mut self, index: usize) {
+        self.counts[index] += 1;
+        if self.counts[index] == 1 {
+            self.update_free(index, false);
+        }
+    }
+
+    pub fn release(&mut self, index: usize) {
+        self.counts[index] -= 1;
+        if self.counts[index] == 0 {
+            self.update_free(index, true);
+        }
+    }
+
+    fn update_free(&mut self, index: usize, free: bool) {
+        let mut node = index + 1;
+        while node < self.free_tree.len() {
+            if free {
+                self.free_tree[node] += 1;
+            } else {
+                self.free_tree[node] -= 1;
+            }
+            node += node & node.wrapping_neg();
+        }
+        if free {
+            self.free_count += 1;
+        } else {
+            self.free_count -= 1;
+        }
+    }
+}
+
+/// The cells covered by a snake, from the tail end to the head; cells off a bounded board are
+/// kept as `None`, so that the positions along the body stay consistent.
+pub(crate) struct Track {
+    cells: VecDeque<Option<usize>>,
+    /// Distance along the body from the tail end to the first cell center.
+    offset: f64,
+    /// How many times the snake covers each cell, for telling whether it bites itself.
+    counts: Vec<u8>,
+}
+
+impl Track {
+    /// Tracks the snake; the cells are added to the occupancy only if the snake is alive.
+    pub fn new(snake: &Snake, bounds: &Bounds, occupancy: &mut Occupancy) -> Track {
+        let body = snake.body();
+        let mut track = Track {
+            cells: VecDeque::new(),
+            offset: tail_offset(body),
+            counts: vec![0; occupancy.counts.len()],
+        };
+        let mut position = track.offset;
+        let mut start = 0_f64;
+        for pair in body.windows(2) {
+            let segment = pair[1].subtract(&pair[0]);
+            let length = segment.length();
+            // A snake turning right at the end of a frame has a zero-length head segment.
+            if length == 0_f64 {
+                continue;
+            }
+            let direction = segment.normalize();
+            while position <= start + length + EPSILON {
+                let center = pair[0].add(&direction.scale_by(position - start));
+                let index = occupancy.index(bounds, cell_of(&center));
+                track.push_back(index, snake.alive, occupancy);
+                position += 1_f64;
+            }
+            start += length;
+        }
+        track
+    }
+
+    /// Updates the cells after the snake advanced, and its tail end moved by the given distance.
+    pub fn advance(
+        &mut self,
+        snake: &Snake,
+        tail_distance: f64,
+        bounds: &Bounds,
+        occupancy: &mut Occupancy,
+    ) {
+        let body = snake.body();
+        // The tail end moved forward by the distance, leaving the centers in between.
+        let offset = tail_offset(body);
+        let left = (tail_distance + offset - self.offset).round().max(0_f64) as usize;
+        for _ in 0..left.min(self.cells.len()) {
+            self.pop_front(occupancy);
+        }
+        self.offset = offset;
+
+        let length = snake.length();
+        let mut position = offset + self.cells.len() as f64;
+        while position <= length + EPSILON {
+            let center = point_before_head(body, length - position);
+            let index = occupancy.index(bounds, cell_of(&center));
+            self.push_back(index, true, occupancy);
+            position += 1_f64;
+        }
+    }
+
+    /// The indices of the covered cells that are on the board, from the tail end to the head.
+    pub fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cells.iter().flatten().copied()
+    }
+
+    /// Removes the cells from the occupancy, when the snake dies; the track itself is kept.
+    pub fn release(&self, occupancy: &mut Occupancy) {
+        for index in self.cells.iter().flatten() {
+            occupancy.release(*index);
+        }
+    }
+
+    /// The distance from the tail end of the cell center covered by the rest of the body, in the
+    /// cell the head is entering, or is in, if there's one; the closest one to the head, if the body
+    /// covers the cell more than once.
+    pub fn bitten_center(
+        &self,
+        snake: &Snake,
+        bounds: &Bounds,
+        occupancy: &Occupancy,
+    ) -> Option<f64> {
+        let index = occupancy.index(bounds, cell_of(&snake.probe()))?;
+        let own = self.cells.back() == Some(&Some(index));
+        if self.counts[index] <= own as u8 {
+            return None;
+        }
+        let rest = self.cells.len() - own as usize;
+        let position = self
+            .cells
+            .range(..rest)
+            .rposition(|&cell| cell == Some(index))?;
+        Some(self.offset + position as f64)
+    }
+
+    fn push_back(&mut self, index: Option<usize>, occupy: bool, occupancy: &mut Occupancy) {
+        if let Some(index) = index {
+            self.counts[index] += 1;
+            if occupy {
+                occupancy.occupy(index);
+            }
+        }
+        self.cells.push_back(index);
+    }
+
+    fn pop_front(&mut self, occupancy: &mut Occupancy) {
+        if let Some(Some(index)) = self.cells.pop_front() {
+            self.counts[index] -= 1;
+            occupancy.release(index);
+        }
+    }
+}
+
+/// The occupancy of the board, and the tracks of the snakes.
+pub(crate) fn track_snakes(
+    bounds: &Bounds,
+    snakes: &[Snake],
+    walls: &BTreeSet<Cell>,
+) -> (Occupancy, Vec<Track>) {
+    let mut occupancy = Occupancy::new(bounds, walls);
+    let tracks = snakes
+        .iter()
+        .map(|snake| Track::new(snake, bounds, &mut occupancy))
+        .collect();
+    (occupancy, tracks)
+}
+
+fn cell_of(point: &Vector) -> Cell {
+    (point.x.floor() as i32, point.y.floor() as i32)
+}
+
+/// Distance from the tail end to the first cell center on the body.
+fn tail_offset(body: &[Vector]) -> f64 {
+    let (tail, next) = (&body[0], &body[1]);
+    let (position, forward) = if (next.x - tail.x).abs() > (next.y - tail.y).abs() {
+        (tail.x, next.x > tail.x)
+    } else {
+        (tail.y, next.y > tail.y)
+    };
+    if forward {
+        (position - 0.5 - EPSILON).ceil() + 0.5 - position
+    } else {
+        position - ((position - 0.5 + EPSILON).floor() + 0.5)
+    }
+}
+
+/// The point on the body at the given distance from the head.
+fn point_before_head(body: &[Vector], distance: f64) -> Vector {
+    let mut remaining = distance;
+    for pair in body.windows(2).rev() {
+        let segment = pair[1].subtract(&pair[0]);
+        let length = segment.length();
+        if remaining <= length {
+            // Zero-length segments are skipped, since they have no direction.
+            if length > 0_f64 {
+                return pair[1].subtract(&segment.scale_by(remaining / length));
+            }
+            continue;
+        }
+        remaining -= length;
+    }
+    body[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    /// The free cells found through the tree are the ones found by scanning the counts, on boards
+    /// whose cell count is a power of two or not, as cells are taken and freed.
+    #[test]
+    fn nth_free() {
+        let mut rng = Pcg32::seed_from_u64(3);
+        for &(width, height) in &[(1, 1), (1, 2), (4, 4), (7, 5), (13, 11), (32, 17)] {
+            let bounds = Bounds {
+                width,
+                height,
+                topology: Topology::Bounded,
+            };
+            let walls = [(0, 0), (width - 1, height - 1)].iter().cloned().collect();
+            let mut occupancy = Occupancy::new(&bounds, &walls);
+            for _ in 0..500 {
+                let free: Vec<usize> = (0..occupancy.counts.len())
+                    .filter(|&index| occupancy.counts[index] == 0)
+                    .collect();
+                assert_eq!(occupancy.free_count(), free.len());
+                for (n, &index) in free.iter().enumerate() {
+                    assert_eq!(occupancy.nth_free(n), index, "{}x{}", width, height);
+                }
+                let index = rng.gen_range(0, occupancy.counts.len());
+                if occupancy.counts[index] > 0 && rng.gen_bool(0.6) {
+                    occupancy.release(index);
+                } else {
+                    occupancy.occupy(index);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Power-ups: collectibles that appear on the board from time to time, and whose effects last for
+//! a while after they're collected.
+//!
+//! An effect is a type implementing `Effect`, which tells how it changes the game while it's
+//! active, and which settings of it are valid; the game only goes through the trait. The power-up
+//! kinds are serialized, in the configs and the saves, as the registered name of their effect
+//! along with its settings, so that they can be built back: effects defined outside the crate take
+//! a call to `register_effect()` before they're deserialized, or created from JS.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::{OnceLock, RwLock};
+
+use rand_pcg::Pcg32;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use wasm_bindgen::prelude::*;
+
+use crate::config::ConfigError;
+use crate::food::{pick_weighted, random_interval};
+use crate::Vector;
+
+/// How collecting an effect that's already active, for the same snake, combines with it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stacking {
+    /// The duration starts over.
+    Refresh,
+    /// The duration is added to the remaining one.
+    Extend,
+    /// The effect applies once more, up to the given number of times, and the duration starts
+    /// over.
+    Stack(u32),
+}
+
+pub trait Effect: EffectObject + Debug + Send + Sync {
+    /// Name the effect is registered under, see `register_effect()`.
+    fn kind(&self) -> &'static str;
+
+    /// Name shown in the HUD.
+    fn name(&self) -> &'static str;
+
+    fn stacking(&self) -> Stacking {
+        Stacking::Refresh
+    }
+
+    /// Factor the speed of the game is multiplied by, while the effect is active.
+    fn speed_factor(&self, _stacks: u32) -> f64 {
+        1_f64
+    }
+
+    /// Factor the points scored by the collecting snake are multiplied by.
+    fn score_factor(&self, _stacks: u32) -> i32 {
+        1
+    }
+
+    /// Whether the collecting snake can go through its own body.
+    fn is_ghost(&self) -> bool {
+        false
+    }
+
+    /// Units trimmed from the tail of the collecting snake, once, when the power-up is collected.
+    fn shrink(&self) -> u32 {
+        0
+    }
+
+    /// Whether the settings of the effect make sense, which the config validation checks.
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+/// What the power-up kinds need of their effect as a trait object; it's implemented for all the
+/// effects that can be cloned and serialized.
+pub trait EffectObject {
+    fn clone_effect(&self) -> Box<dyn Effect>;
+
+    /// The settings of the effect, which its registered constructor builds it back from.
+    fn settings(&self) -> serde_json::Value;
+}
+
+impl<E: Effect + Clone + Serialize + 'static> EffectObject for E {
+    fn clone_effect(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
+}
+
+impl Clone for Box<dyn Effect> {
+    fn clone(&self) -> Box<dyn Effect> {
+        self.clone_effect()
+    }
+}
+
+/// Builds an effect from its settings, failing with a readable message if they don't fit it.
+pub type EffectConstructor = fn(serde_json::Value) -> Result<Box<dyn Effect>, String>;
+
+/// The effects that can be deserialized, by the name they're registered under; the ones of the
+/// crate are always there.
+fn registry() -> &'static RwLock<BTreeMap<&'static str, EffectConstructor>> {
+    static REGISTRY: OnceLock<RwLock<BTreeMap<&'static str, EffectConstructor>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut effects = BTreeMap::new();
+        effects.insert("Speed", construct::<SpeedChange> as EffectConstructor);
+        effects.insert("Ghost", construct::<Ghost>);
+        effects.insert("Shrink", construct::<Shrink>);
+        effects.insert("ScoreMultiplier", construct::<ScoreMultiplier>);
+        RwLock::new(effects)
+    })
+}
+
+fn construct<E: Effect + DeserializeOwned + 'static>(
+    settings: serde_json::Value,
+) -> Result<Box<dyn Effect>, String> {
+    let effect: E = serde_json::from_value(settings).map_err(|error| error.to_string())?;
+    Ok(Box::new(effect))
+}
+
+/// Registers the effect type under the given name, which its `Effect::kind()` must return, so that
+/// the power-up kinds using it can be deserialized; an effect already registered under the name is
+/// replaced.
+pub fn register_effect<E: Effect + DeserializeOwned + 'static>(kind: &'static str) {
+    registry().write().unwrap().insert(kind, construct::<E>);
+}
+
+/// The effect registered under the given name, with the given settings.
+pub fn create_effect(kind: &str, settings: serde_json::Value) -> Result<Box<dyn Effect>, String> {
+    let constructor = registry()
+        .read()
+        .unwrap()
+        .get(kind)
+        .copied()
+        .ok_or_else(|| format!("unknown effect: {}", kind))?;
+    constructor(settings)
+}
+
+/// Changes the speed of the game: a factor above 1 is a boost, below 1 a slowdown.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpeedChange {
+    pub factor: f64,
+}
+
+impl Effect for SpeedChange {
+    fn kind(&self) -> &'static str {
+        "Speed"
+    }
+
+    fn name(&self) -> &'static str {
+        if self.factor < 1_f64 {
+            "slowdown"
+        } else {
+            "speed boost"
+        }
+    }
+
+    fn stacking(&self) -> Stacking {
+        Stacking::Stack(3)
+    }
+
+    fn speed_factor(&self, stacks: u32) -> f64 {
+        self.factor.powi(stacks as i32)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.factor.is_finite() && self.factor > 0_f64
+    }
+}
+
+/// Lets the snake go through its own body.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ghost;
+
+impl Effect for Ghost {
+    fn kind(&self) -> &'static str {
+        "Ghost"
+    }
+
+    fn name(&self) -> &'static str {
+        "ghost"
+    }
+
+    fn stacking(&self) -> Stacking {
+        Stacking::Extend
+    }
+
+    fn is_ghost(&self) -> bool {
+        true
+    }
+}
+
+/// Trims the tail of the snake, leaving at least one unit.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Shrink {
+    pub units: u32,
+}
+
+impl Effect for Shrink {
+    fn kind(&self) -> &'static str {
+        "Shrink"
+    }
+
+    fn name(&self) -> &'static str {
+        "shrink"
+    }
+
+    fn shrink(&self) -> u32 {
+        self.units
+    }
+}
+
+/// Multiplies the points the snake scores.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScoreMultiplier {
+    pub factor: i32,
+}
+
+impl Effect for ScoreMultiplier {
+    fn kind(&self) -> &'static str {
+        "ScoreMultiplier"
+    }
+
+    fn name(&self) -> &'static str {
+        "score multiplier"
+    }
+
+    fn score_factor(&self, _stacks: u32) -> i32 {
+        self.factor
+    }
+
+    fn is_valid(&self) -> bool {
+        self.factor > 0
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PowerUpKind {
+    #[serde(with = "serialized_effect")]
+    effect: Box<dyn Effect>,
+    /// Milliseconds the effect lasts once collected; if zero, the effect only applies when it's
+    /// collected (e.g. shrinking).
+    pub duration: f64,
+    /// How likely the kind is to be picked when a power-up spawns, relative to the other kinds.
+    pub weight: f64,
+}
+
+#[wasm_bindgen]
+impl PowerUpKind {
+    pub fn speed(factor: f64, duration: f64, weight: f64) -> PowerUpKind {
+        PowerUpKind::new(Box::new(SpeedChange { factor }), duration, weight)
+    }
+
+    pub fn ghost(duration: f64, weight: f64) -> PowerUpKind {
+        PowerUpKind::new(Box::new(Ghost), duration, weight)
+    }
+
+    pub fn shrink(units: u32, weight: f64) -> PowerUpKind {
+        PowerUpKind::new(Box::new(Shrink { units }), 0_f64, weight)
+    }
+
+    pub fn score_multiplier(factor: i32, duration: f64, weight: f64) -> PowerUpKind {
+        PowerUpKind::new(Box::new(ScoreMultiplier { factor }), duration, weight)
+    }
+
+    /// Kind whose effect is the one registered under the given name, with the given settings, as
+    /// JSON; fails with a readable message if there's no such effect, or the settings don't fit it.
+    pub fn custom(
+        effect: &str,
+        settings: &str,
+        duration: f64,
+        weight: f64,
+    ) -> Result<PowerUpKind, JsValue> {
+        let settings = serde_json::from_str(settings)
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        let effect = create_effect(effect, settings).map_err(|error| JsValue::from_str(&error))?;
+        Ok(PowerUpKind::new(effect, duration, weight))
+    }
+
+    pub fn name(&self) -> String {
+        self.effect().name().to_string()
+    }
+}
+
+impl PowerUpKind {
+    pub fn new(effect: Box<dyn Effect>, duration: f64, weight: f64) -> PowerUpKind {
+        PowerUpKind {
+            effect,
+            duration,
+            weight,
+        }
+    }
+
+    pub fn effect(&self) -> &dyn Effect {
+        self.effect.as_ref()
+    }
+}
+
+/// Kinds are equal if their effects are of the same kind, with the same settings.
+impl PartialEq for PowerUpKind {
+    fn eq(&self, other: &PowerUpKind) -> bool {
+        self.effect.kind() == other.effect.kind()
+            && self.effect.settings() == other.effect.settings()
+            && self.duration == other.duration
+            && self.weight == other.weight
+    }
+}
+
+/// The effect of a power-up kind is serialized as its registered name along with its settings: in
+/// JSON, as an object with the name as its single key, which is also how the closed set of effects
+/// of the older saves was serialized; in the binary formats, which can't hold arbitrary settings,
+/// as the name and the settings as JSON.
+mod serialized_effect {
+    use super::*;
+
+    // The field type is what `#[serde(with)]` passes.
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(
+        effect: &Box<dyn Effect>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let settings = effect.settings();
+        if serializer.is_human_readable() {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry(effect.kind(), &settings)?;
+            map.end()
+        } else {
+            (effect.kind(), settings.to_string()).serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<dyn Effect>, D::Error> {
+        let (kind, settings) = if deserializer.is_human_readable() {
+            let effect = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+            if effect.len() != 1 {
+                return Err(D::Error::custom("an effect must have a single name"));
+            }
+            effect.into_iter().next().unwrap()
+        } else {
+            let (kind, settings) = <(String, String)>::deserialize(deserializer)?;
+            let settings = serde_json::from_str(&settings).map_err(D::Error::custom)?;
+            (kind, settings)
+        };
+        create_effect(&kind, settings).map_err(D::Error::custom)
+    }
+}
+
+/// Power-ups appear one at a time, at random intervals, and disappear if they aren't collected in
+/// time.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PowerUpRules {
+    /// Milliseconds from the start of the game, or from the last power-up going away, until the
+    /// next one appears; picked at random between the two.
+    pub min_interval: f64,
+    pub max_interval: f64,
+    /// Milliseconds a power-up stays on the board.
+    pub lifetime: f64,
+    pub(crate) kinds: Vec<PowerUpKind>,
+}
+
+#[wasm_bindgen]
+impl PowerUpRules {
+    /// Rules without kinds, which must be added via `kind()`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(min_interval: f64, max_interval: f64, lifetime: f64) -> PowerUpRules {
+        PowerUpRules {
+            min_interval,
+            max_interval,
+            lifetime,
+            kinds: Vec::new(),
+        }
+    }
+
+    pub fn kind(mut self, kind: &PowerUpKind) -> PowerUpRules {
+        self.kinds.push(kind.clone());
+        self
+    }
+}
+
+impl PowerUpRules {
+    pub fn kinds(&self) -> &[PowerUpKind] {
+        &self.kinds
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ConfigError> {
+        let (min, max) = (self.min_interval, self.max_interval);
+        if !(min.is_finite() && max.is_finite() && min >= 0_f64 && min <= max) {
+            return Err(ConfigError::InvalidPowerUpInterval { min, max });
+        }
+        if !(self.lifetime.is_finite() && self.lifetime > 0_f64) {
+            return Err(ConfigError::InvalidPowerUpLifetime(self.lifetime));
+        }
+        if self.kinds.is_empty() {
+            return Err(ConfigError::NoPowerUpKinds);
+        }
+        for (index, kind) in self.kinds.iter().enumerate() {
+            if !(kind.weight.is_finite()
+                && kind.weight > 0_f64
+                && kind.duration.is_finite()
+                && kind.duration >= 0_f64
+                && kind.effect().is_valid())
+            {
+                return Err(ConfigError::InvalidPowerUpKind(index));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn next_interval(&self, rng: &mut Pcg32) -> f64 {
+        random_interval(self.min_interval, self.max_interval, rng)
+    }
+
+    /// A power-up of a random kind, at the given position.
+    pub(crate) fn create_power_up(&self, position: Vector, rng: &mut Pcg32) -> PowerUp {
+        PowerUp {
+            position,
+            kind: pick_weighted(self.kinds.iter().map(|kind| kind.weight), rng),
+            remaining: self.lifetime,
+        }
+    }
+}
+
+/// A power-up on the board.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct PowerUp {
+    /// Always a cell center.
+    pub position: Vector,
+    /// Index of the kind, in the order they've been added to the rules.
+    pub kind: usize,
+    /// Milliseconds before the power-up disappears.
+    pub remaining: f64,
+}
+
+/// The effect of a collected power-up, while it lasts.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct ActiveEffect {
+    /// Index of the power-up kind.
+    pub kind: usize,
+    /// The snake that collected it.
+    pub player: usize,
+    /// Milliseconds before the effect ends.
+    pub remaining: f64,
+    pub stacks: u32,
+}
+
+/// What the HUD shows of an active effect.
+#[derive(Serialize)]
+pub(crate) struct EffectStatus {
+    pub name: &'static str,
+    pub player: usize,
+    pub remaining: f64,
+    pub stacks: u32,
+}
+
+/// Adds the effect of a power-up of the given kind, just collected, to the active ones, following
+/// its stacking rule.
+pub(crate) fn activate(
+    effects: &mut Vec<ActiveEffect>,
+    kinds: &[PowerUpKind],
+    kind: usize,
+    player: usize,
+    remaining: f64,
+) {
+    let active = effects
+        .iter_mut()
+        .find(|effect| effect.kind == kind && effect.player == player);
+    match (active, kinds[kind].effect().stacking()) {
+        (Some(active), Stacking::Refresh) => active.remaining = remaining,
+        (Some(active), Stacking::Extend) => active.remaining += remaining,
+        (Some(active), Stacking::Stack(max)) => {
+            active.remaining = remaining;
+            active.stacks = (active.stacks + 1).min(max);
+        }
+        (None, _) => effects.push(ActiveEffect {
+            kind,
+            player,
+            remaining,
+            stacks: 1,
+        }),
+    }
+}
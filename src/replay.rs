@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use rand::Rng;
+
+use crate::config::{ConfigError, GameConfig};
+use crate::level::{Level, LevelError};
+use crate::{Game, Movement, TurnRejection};
+
+/// Version of the replay format; see `save::SAVE_VERSION` for the compatibility rules. Unlike a
+/// save, a replay also depends on the simulation: bump it whenever the same inputs give a different
+/// game, and raise `MIN_REPLAY_VERSION` to it.
+pub const REPLAY_VERSION: u32 = 8;
+
+/// Oldest version whose replays still play out as they were recorded; the older ones were recorded
+/// before the sweep through the frames, the food placement among the free cells, and the growth
+/// along the travelled path, and would go out of sync.
+pub const MIN_REPLAY_VERSION: u32 = 8;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Frame {
+    pub timespan: f64,
+    pub movement: Option<Movement>,
+    /// The `Game::steer()` calls preceding the frame.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steers: Vec<(usize, Option<Movement>)>,
+    /// The turns accepted by `Game::enqueue_turn()` before the frame.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub turns: Vec<(usize, Movement)>,
+}
+
+/// The arguments a game has been created with, and all the inputs passed to `Game::process`, which
+/// are enough to reproduce the game exactly.
+#[wasm_bindgen]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    /// Always has a seed.
+    #[serde(flatten)]
+    config: GameConfig,
+    /// Level map the game has been created from; if present, it overrides the board and snake
+    /// settings of the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    level: Option<String>,
+    frames: Vec<Frame>,
+}
+
+#[wasm_bindgen]
+impl Replay {
+    pub fn create_game(&self) -> Game {
+        // The config and the level are validated when they're set.
+        match &self.level {
+            Some(level) => {
+                let level = Level::parse(level).unwrap();
+                Game::with_level_config(&level, &self.config, self.config.seed.unwrap())
+            }
+            None => Game::with_config(&self.config).unwrap(),
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Milliseconds the given frame lasts, for playing the replay back in real time.
+    pub fn frame_timespan(&self, index: usize) -> f64 {
+        self.frames[index].timespan
+    }
+
+    /// Feeds the given frame to a game created by `create_game()`; frames must be applied in order.
+    pub fn apply_frame(&self, game: &mut Game, index: usize) {
+        let frame = &self.frames[index];
+        for &(player, movement) in &frame.steers {
+            game.steer(player, movement);
+        }
+        for &(player, movement) in &frame.turns {
+            game.enqueue_turn(player, movement);
+        }
+        game.process(frame.timespan, frame.movement);
+    }
+
+    /// Plays all the frames, returning the final state of the game.
+    pub fn run(&self) -> Game {
+        let mut game = self.create_game();
+        for index in 0..self.frames.len() {
+            self.apply_frame(&mut game, index);
+        }
+        game
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Replay, JsValue> {
+        Replay::decode_json(json).map_err(|error| JsValue::from_str(&error))
+    }
+}
+
+impl Replay {
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn decode_json(json: &str) -> Result<Replay, String> {
+        let replay: Replay = serde_json::from_str(json).map_err(|error| error.to_string())?;
+        if replay.version > REPLAY_VERSION {
+            return Err(format!("unsupported replay version: {}", replay.version));
+        }
+        if replay.version < MIN_REPLAY_VERSION {
+            return Err(format!(
+                "outdated replay version: {}, recorded with an older simulation",
+                replay.version
+            ));
+        }
+        if replay.config.seed.is_none() {
+            return Err("the seed is missing".to_string());
+        }
+        replay
+            .config
+            .validate()
+            .map_err(|error| format!("invalid config: {}", error))?;
+        if let Some(level) = &replay.level {
+            Level::parse(level).map_err(|error| format!("invalid level: {}", error))?;
+        }
+        Ok(replay)
+    }
+}
+
+/// Captures the inputs of a game, so that it can be saved as a `Replay`.
+#[wasm_bindgen]
+pub struct Recorder {
+    replay: Replay,
+    /// Steers of the frame being recorded.
+    steers: Vec<(usize, Option<Movement>)>,
+    /// Accepted turns of the frame being recorded.
+    turns: Vec<(usize, Movement)>,
+}
+
+#[wasm_bindgen]
+impl Recorder {
+    /// Fails with a readable message if the config is invalid; if it has no seed, a random one is
+    /// picked.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &GameConfig) -> Result<Recorder, JsValue> {
+        Recorder::with_config(config).map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Level map of the game created by `create_game()`; see `Game::from_level()`.
+    pub fn set_level(&mut self, level: &str) -> Result<(), JsValue> {
+        self.try_set_level(level)
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Creates the game to record, from the recorder arguments.
+    pub fn create_game(&self) -> Game {
+        self.replay.create_game()
+    }
+
+    /// Records the movement, then forwards it to `Game::steer`.
+    pub fn steer(&mut self, game: &mut Game, player: usize, movement: Option<Movement>) {
+        self.steers.push((player, movement));
+        game.steer(player, movement);
+    }
+
+    /// Forwards the turn to `Game::enqueue_turn`, recording it if accepted.
+    pub fn enqueue_turn(
+        &mut self,
+        game: &mut Game,
+        player: usize,
+        movement: Movement,
+    ) -> Option<TurnRejection> {
+        let rejection = game.enqueue_turn(player, movement);
+        if rejection.is_none() {
+            self.turns.push((player, movement));
+        }
+        rejection
+    }
+
+    /// Records the inputs, then forwards them to `Game::process`.
+    pub fn process(&mut self, game: &mut Game, timespan: f64, movement: Option<Movement>) {
+        self.replay.frames.push(Frame {
+            timespan,
+            movement,
+            steers: std::mem::take(&mut self.steers),
+            turns: std::mem::take(&mut self.turns),
+        });
+        game.process(timespan, movement);
+    }
+
+    /// Records the inputs as one frame per fixed step, then forwards them to `Game::advance`; the
+    /// steers repeat in each frame, as they last for the whole call, while the turns, which stay
+    /// queued, are recorded with the first frame that runs.
+    pub fn advance(&mut self, game: &mut Game, elapsed: f64, movement: Option<Movement>) -> u32 {
+        let steps = game.take_steps(elapsed);
+        let steers = std::mem::take(&mut self.steers);
+        for _ in 0..steps {
+            self.replay.frames.push(Frame {
+                timespan: game.fixed_step,
+                movement,
+                steers: steers.clone(),
+                turns: std::mem::take(&mut self.turns),
+            });
+        }
+        game.run_steps(steps, movement);
+        steps
+    }
+
+    pub fn get_replay(&self) -> Replay {
+        self.replay.clone()
+    }
+}
+
+impl Recorder {
+    /// Native equivalent of `new()`.
+    pub fn with_config(config: &GameConfig) -> Result<Recorder, ConfigError> {
+        config.validate()?;
+        let mut config = config.clone();
+        config.seed = config.seed.or_else(|| Some(rand::thread_rng().gen()));
+        Ok(Recorder {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                config,
+                level: None,
+                frames: Vec::new(),
+            },
+            steers: Vec::new(),
+            turns: Vec::new(),
+        })
+    }
+
+    /// Native equivalent of `set_level()`.
+    pub fn try_set_level(&mut self, level: &str) -> Result<(), LevelError> {
+        Level::parse(level)?;
+        self.replay.level = Some(level.to_string());
+        Ok(())
+    }
+}
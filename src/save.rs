@@ -0,0 +1,941 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::convert::TryInto;
+use std::fmt;
+
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+use crate::config::MAX_BOARD_SIDE;
+use crate::difficulty::SpeedCurve;
+use crate::events::DeathCause;
+use crate::food::{Bonus, Food, FoodKind, FoodRules};
+use crate::occupancy;
+use crate::power_up::{
+    ActiveEffect, Effect, Ghost, PowerUp, PowerUpKind, PowerUpRules, ScoreMultiplier, Shrink,
+    SpeedChange,
+};
+use crate::snake::{Snake, SnakeBody};
+use crate::topology::{Bounds, Topology};
+use crate::{
+    Cell, Game, GameStatus, Movement, Vector, DEFAULT_FIXED_STEP, DEFAULT_TURN_QUEUE_DEPTH,
+};
+
+/// Version of the save format. Bump it whenever `SavedGame` changes; new fields can have a
+/// `#[serde(default)]`, so that older JSON saves keep loading, but older binary saves need a
+/// frozen copy of their struct to be decoded from.
+pub const SAVE_VERSION: u32 = 12;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Json(String),
+    Binary(String),
+    UnsupportedVersion(u32),
+    Invalid(&'static str),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Json(message) => write!(f, "invalid JSON save: {}", message),
+            SaveError::Binary(message) => write!(f, "invalid binary save: {}", message),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "unsupported save version: {}", version)
+            }
+            SaveError::Invalid(message) => write!(f, "invalid save: {}", message),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    speed_curve: Option<SpeedCurve>,
+    snakes: Vec<Snake>,
+    /// The `growth` of each snake, which the snakes don't serialize.
+    growth: Vec<f64>,
+    turns: Vec<VecDeque<Movement>>,
+    turn_queue_depth: usize,
+    foods: Vec<Food>,
+    food_rules: FoodRules,
+    bonus: Option<Bonus>,
+    next_bonus: f64,
+    power_up_rules: Option<PowerUpRules>,
+    power_up: Option<PowerUp>,
+    next_power_up: f64,
+    effects: Vec<ActiveEffect>,
+    clock: f64,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    status: GameStatus,
+    seed: u64,
+    rng: Pcg32,
+    fixed_step: f64,
+    accumulator: f64,
+}
+
+impl SavedGame {
+    fn from_game(game: &Game) -> SavedGame {
+        SavedGame {
+            version: SAVE_VERSION,
+            width: game.width,
+            height: game.height,
+            speed: game.speed,
+            speed_curve: game.speed_curve,
+            snakes: game.snakes.clone(),
+            growth: game.snakes.iter().map(|snake| snake.growth).collect(),
+            turns: game.turns.clone(),
+            turn_queue_depth: game.turn_queue_depth,
+            foods: game.foods.clone(),
+            food_rules: game.food_rules.clone(),
+            bonus: game.bonus,
+            next_bonus: game.next_bonus,
+            power_up_rules: game.power_up_rules.clone(),
+            power_up: game.power_up,
+            next_power_up: game.next_power_up,
+            effects: game.effects.clone(),
+            clock: game.clock,
+            topology: game.topology,
+            walls: game.walls.clone(),
+            status: game.status,
+            seed: game.seed,
+            rng: game.rng.clone(),
+            fixed_step: game.fixed_step,
+            accumulator: game.accumulator,
+        }
+    }
+
+    fn into_game(mut self) -> Result<Game, SaveError> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(SaveError::Invalid("the board must not be empty"));
+        }
+        if self.width > MAX_BOARD_SIDE || self.height > MAX_BOARD_SIDE {
+            return Err(SaveError::Invalid("the board is too large"));
+        }
+        if !(self.speed.is_finite() && self.speed > 0_f64) {
+            return Err(SaveError::Invalid("the speed must be a positive number"));
+        }
+        if self.turn_queue_depth == 0 {
+            return Err(SaveError::Invalid(
+                "the turn queue must hold at least one turn",
+            ));
+        }
+        if self.snakes.is_empty() {
+            return Err(SaveError::Invalid("there must be at least one snake"));
+        }
+        if self.turns.len() != self.snakes.len() || self.growth.len() != self.snakes.len() {
+            return Err(SaveError::Invalid(
+                "each snake must have its queue of turns and its growth",
+            ));
+        }
+        if self
+            .growth
+            .iter()
+            .any(|growth| !(growth.is_finite() && *growth >= 0_f64))
+        {
+            return Err(SaveError::Invalid("the growth must not be negative"));
+        }
+        for (snake, &growth) in self.snakes.iter_mut().zip(&self.growth) {
+            snake.growth = growth;
+        }
+        if self.snakes.iter().any(|snake| snake.body().len() < 2) {
+            return Err(SaveError::Invalid(
+                "the snakes must have at least two points",
+            ));
+        }
+        if self.food_rules.count == 0 || self.food_rules.kinds.is_empty() {
+            return Err(SaveError::Invalid(
+                "there must be at least one food kind and item",
+            ));
+        }
+        let kinds = self.food_rules.kinds.len();
+        if self.foods.iter().any(|food| food.kind >= kinds) {
+            return Err(SaveError::Invalid("the food kinds must exist"));
+        }
+        let (width, height) = (self.width, self.height);
+        if self
+            .walls
+            .iter()
+            .any(|&(x, y)| x < 0 || x >= width || y < 0 || y >= height)
+        {
+            return Err(SaveError::Invalid("the walls must be on the board"));
+        }
+        let is_on_board = |point: &Vector| {
+            point.x > 0_f64
+                && point.x < f64::from(width)
+                && point.y > 0_f64
+                && point.y < f64::from(height)
+        };
+        let mut items = self
+            .foods
+            .iter()
+            .map(|food| &food.position)
+            .chain(self.bonus.iter().map(|bonus| &bonus.position))
+            .chain(self.power_up.iter().map(|power_up| &power_up.position));
+        if !items.all(is_on_board) {
+            return Err(SaveError::Invalid(
+                "the food, the bonus and the power-up must be on the board",
+            ));
+        }
+        let kinds = self
+            .power_up_rules
+            .as_ref()
+            .map_or(0, |rules| rules.kinds().len());
+        let power_up_kinds = self.power_up.iter().map(|power_up| power_up.kind);
+        let effect_kinds = self.effects.iter().map(|effect| effect.kind);
+        if power_up_kinds.chain(effect_kinds).any(|kind| kind >= kinds) {
+            return Err(SaveError::Invalid("the power-up kinds must exist"));
+        }
+        if self
+            .effects
+            .iter()
+            .any(|effect| effect.player >= self.snakes.len())
+        {
+            return Err(SaveError::Invalid("the effects must belong to a snake"));
+        }
+        if self
+            .effects
+            .iter()
+            .any(|effect| !(effect.remaining.is_finite() && effect.remaining >= 0_f64))
+        {
+            return Err(SaveError::Invalid(
+                "the effects must have a non-negative time left",
+            ));
+        }
+
+        // A step that isn't positive is kept as it is, since `advance()` just doesn't run with it.
+        if !(self.fixed_step.is_finite() && self.accumulator.is_finite()) {
+            return Err(SaveError::Invalid(
+                "the fixed step and the time accumulated for it must be finite",
+            ));
+        }
+
+        let bounds = Bounds {
+            width: self.width,
+            height: self.height,
+            topology: self.topology,
+        };
+        let (occupancy, tracks) = occupancy::track_snakes(&bounds, &self.snakes, &self.walls);
+
+        Ok(Game {
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            speed_curve: self.speed_curve,
+            movements: vec![None; self.snakes.len()],
+            turns: self.turns,
+            turn_queue_depth: self.turn_queue_depth,
+            snakes: self.snakes,
+            foods: self.foods,
+            food_rules: self.food_rules,
+            bonus: self.bonus,
+            next_bonus: self.next_bonus,
+            power_up_rules: self.power_up_rules,
+            power_up: self.power_up,
+            next_power_up: self.next_power_up,
+            effects: self.effects,
+            clock: self.clock,
+            topology: self.topology,
+            walls: self.walls,
+            seed: self.seed,
+            rng: self.rng,
+            occupancy,
+            tracks,
+            status: self.status,
+            events: Vec::new(),
+            fixed_step: self.fixed_step,
+            accumulator: self.accumulator,
+            snake_buffer: Vec::new(),
+            food_buffer: Vec::new(),
+            interpolated: SnakeBody::default(),
+        })
+    }
+}
+
+/// Format whose power-up effects were a closed set, serialized as `EffectKindV11`.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV11 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    speed_curve: Option<SpeedCurve>,
+    snakes: Vec<Snake>,
+    /// The `growth` of each snake, which the snakes don't serialize.
+    growth: Vec<f64>,
+    turns: Vec<VecDeque<Movement>>,
+    turn_queue_depth: usize,
+    foods: Vec<Food>,
+    food_rules: FoodRules,
+    bonus: Option<Bonus>,
+    next_bonus: f64,
+    power_up_rules: Option<PowerUpRulesV11>,
+    power_up: Option<PowerUp>,
+    next_power_up: f64,
+    effects: Vec<ActiveEffect>,
+    clock: f64,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    status: GameStatus,
+    seed: u64,
+    rng: Pcg32,
+    fixed_step: f64,
+    accumulator: f64,
+}
+
+impl SavedGameV11 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        SavedGame {
+            version: 11,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            speed_curve: self.speed_curve,
+            snakes: self.snakes,
+            growth: self.growth,
+            turns: self.turns,
+            turn_queue_depth: self.turn_queue_depth,
+            foods: self.foods,
+            food_rules: self.food_rules,
+            bonus: self.bonus,
+            next_bonus: self.next_bonus,
+            power_up_rules: self.power_up_rules.map(PowerUpRulesV11::into_rules),
+            power_up: self.power_up,
+            next_power_up: self.next_power_up,
+            effects: self.effects,
+            clock: self.clock,
+            topology: self.topology,
+            walls: self.walls,
+            status: self.status,
+            seed: self.seed,
+            rng: self.rng,
+            fixed_step: self.fixed_step,
+            accumulator: self.accumulator,
+        }
+        .into_game()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PowerUpRulesV11 {
+    min_interval: f64,
+    max_interval: f64,
+    lifetime: f64,
+    kinds: Vec<PowerUpKindV11>,
+}
+
+impl PowerUpRulesV11 {
+    fn into_rules(self) -> PowerUpRules {
+        self.kinds.into_iter().fold(
+            PowerUpRules::new(self.min_interval, self.max_interval, self.lifetime),
+            |rules, kind| {
+                let effect: Box<dyn Effect> = match kind.effect {
+                    EffectKindV11::Speed(effect) => Box::new(effect),
+                    EffectKindV11::Ghost(effect) => Box::new(effect),
+                    EffectKindV11::Shrink(effect) => Box::new(effect),
+                    EffectKindV11::ScoreMultiplier(effect) => Box::new(effect),
+                };
+                rules.kind(&PowerUpKind::new(effect, kind.duration, kind.weight))
+            },
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PowerUpKindV11 {
+    effect: EffectKindV11,
+    duration: f64,
+    weight: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+enum EffectKindV11 {
+    Speed(SpeedChange),
+    Ghost(Ghost),
+    Shrink(Shrink),
+    ScoreMultiplier(ScoreMultiplier),
+}
+
+/// Format without queued turns.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV10 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    speed_curve: Option<SpeedCurve>,
+    snakes: Vec<Snake>,
+    foods: Vec<Food>,
+    food_rules: FoodRules,
+    bonus: Option<Bonus>,
+    next_bonus: f64,
+    power_up_rules: Option<PowerUpRulesV11>,
+    power_up: Option<PowerUp>,
+    next_power_up: f64,
+    effects: Vec<ActiveEffect>,
+    clock: f64,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    status: GameStatus,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV10 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        SavedGameV11 {
+            version: 10,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            speed_curve: self.speed_curve,
+            growth: vec![0_f64; self.snakes.len()],
+            turns: vec![VecDeque::new(); self.snakes.len()],
+            turn_queue_depth: DEFAULT_TURN_QUEUE_DEPTH,
+            snakes: self.snakes,
+            foods: self.foods,
+            food_rules: self.food_rules,
+            bonus: self.bonus,
+            next_bonus: self.next_bonus,
+            power_up_rules: self.power_up_rules,
+            power_up: self.power_up,
+            next_power_up: self.next_power_up,
+            effects: self.effects,
+            clock: self.clock,
+            topology: self.topology,
+            walls: self.walls,
+            status: self.status,
+            seed: self.seed,
+            rng: self.rng,
+            fixed_step: DEFAULT_FIXED_STEP,
+            accumulator: 0_f64,
+        }
+        .into_game()
+    }
+}
+
+/// Format without speed curve.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV9 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    foods: Vec<Food>,
+    food_rules: FoodRules,
+    bonus: Option<Bonus>,
+    next_bonus: f64,
+    power_up_rules: Option<PowerUpRulesV11>,
+    power_up: Option<PowerUp>,
+    next_power_up: f64,
+    effects: Vec<ActiveEffect>,
+    clock: f64,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    status: GameStatus,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV9 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        SavedGameV10 {
+            version: 9,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            speed_curve: None,
+            snakes: self.snakes,
+            foods: self.foods,
+            food_rules: self.food_rules,
+            bonus: self.bonus,
+            next_bonus: self.next_bonus,
+            power_up_rules: self.power_up_rules,
+            power_up: self.power_up,
+            next_power_up: self.next_power_up,
+            effects: self.effects,
+            clock: self.clock,
+            topology: self.topology,
+            walls: self.walls,
+            status: self.status,
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()
+    }
+}
+
+/// Format without power-ups.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV8 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    foods: Vec<Food>,
+    food_rules: FoodRules,
+    bonus: Option<Bonus>,
+    next_bonus: f64,
+    clock: f64,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    status: GameStatus,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV8 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        SavedGameV9 {
+            version: 8,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: self.snakes,
+            foods: self.foods,
+            food_rules: self.food_rules,
+            bonus: self.bonus,
+            next_bonus: self.next_bonus,
+            power_up_rules: None,
+            power_up: None,
+            next_power_up: 0_f64,
+            effects: Vec::new(),
+            clock: self.clock,
+            topology: self.topology,
+            walls: self.walls,
+            status: self.status,
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()
+    }
+}
+
+/// Food rules without bonus.
+#[derive(Serialize, Deserialize)]
+struct FoodRulesV7 {
+    count: usize,
+    kinds: Vec<FoodKind>,
+}
+
+/// Format without bonus food.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV7 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    foods: Vec<Food>,
+    food_rules: FoodRulesV7,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    status: GameStatus,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV7 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        SavedGameV8 {
+            version: 7,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: self.snakes,
+            foods: self.foods,
+            food_rules: FoodRules {
+                count: self.food_rules.count,
+                kinds: self.food_rules.kinds,
+                bonus: None,
+            },
+            bonus: None,
+            next_bonus: 0_f64,
+            clock: 0_f64,
+            topology: self.topology,
+            walls: self.walls,
+            status: self.status,
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()
+    }
+}
+
+/// Format with a single food item, of the default kind.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV6 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    food: Option<Vector>,
+    #[serde(default)]
+    topology: Topology,
+    #[serde(default)]
+    walls: BTreeSet<Cell>,
+    status: GameStatus,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV6 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        let food_rules = FoodRulesV7 {
+            count: 1,
+            kinds: vec![FoodKind::default()],
+        };
+        let foods = self
+            .food
+            .map(|position| Food {
+                position,
+                kind: 0,
+                remaining: None,
+            })
+            .into_iter()
+            .collect();
+        SavedGameV7 {
+            version: 6,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: self.snakes,
+            foods,
+            food_rules,
+            topology: self.topology,
+            walls: self.walls,
+            status: self.status,
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()
+    }
+}
+
+/// Loss cause without the details of the death.
+#[derive(Serialize, Deserialize)]
+enum DeathCauseV5 {
+    Wall,
+    Obstacle,
+    SelfCollision,
+    OtherSnake,
+    HeadToHead,
+}
+
+#[derive(Serialize, Deserialize)]
+enum GameStatusV5 {
+    Running,
+    Won,
+    Lost(DeathCauseV5),
+}
+
+/// Format where the loss cause had no details.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV5 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    food: Option<Vector>,
+    #[serde(default)]
+    topology: Topology,
+    #[serde(default)]
+    walls: BTreeSet<Cell>,
+    status: GameStatusV5,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV5 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        let won = matches!(self.status, GameStatusV5::Won);
+        let mut game = SavedGameV6 {
+            version: 5,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: self.snakes,
+            food: self.food,
+            topology: self.topology,
+            walls: self.walls,
+            status: if won {
+                GameStatus::Won
+            } else {
+                GameStatus::Running
+            },
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()?;
+        if !won {
+            recover_status(&mut game);
+        }
+        Ok(game)
+    }
+}
+
+/// Format without status, where the food was always present.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV4 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    food: Vector,
+    topology: Topology,
+    walls: BTreeSet<Cell>,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV4 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        let mut game = SavedGameV6 {
+            version: 4,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: self.snakes,
+            food: Some(self.food),
+            topology: self.topology,
+            walls: self.walls,
+            status: GameStatus::Running,
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()?;
+        recover_status(&mut game);
+        Ok(game)
+    }
+}
+
+/// Format without walls.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV3 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    food: Vector,
+    topology: Topology,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV3 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        SavedGameV4 {
+            version: 3,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: self.snakes,
+            food: self.food,
+            topology: self.topology,
+            walls: BTreeSet::new(),
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()
+    }
+}
+
+/// Format without topology.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV2 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snakes: Vec<Snake>,
+    food: Vector,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV2 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        SavedGameV3 {
+            version: 2,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: self.snakes,
+            food: self.food,
+            topology: Topology::Bounded,
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()
+    }
+}
+
+/// Single-snake format.
+#[derive(Serialize, Deserialize)]
+struct SavedGameV1 {
+    version: u32,
+    width: i32,
+    height: i32,
+    speed: f64,
+    snake: Vec<Vector>,
+    direction: Vector,
+    food: Vector,
+    score: i32,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SavedGameV1 {
+    fn into_game(self) -> Result<Game, SaveError> {
+        let snake = Snake {
+            body: self.snake.into(),
+            direction: self.direction,
+            score: self.score,
+            alive: true,
+            growth: 0_f64,
+        };
+        let mut game = SavedGameV2 {
+            version: 1,
+            width: self.width,
+            height: self.height,
+            speed: self.speed,
+            snakes: vec![snake],
+            food: self.food,
+            seed: self.seed,
+            rng: self.rng,
+        }
+        .into_game()?;
+        // V1 didn't store whether the snake was dead.
+        game.process_collisions(0_f64);
+        game.drain_events();
+        Ok(game)
+    }
+}
+
+/// Sets the status of a running game whose snakes may be dead, as saved by formats without it, or
+/// without the details of the loss cause.
+fn recover_status(game: &mut Game) {
+    // Dead snakes lie where they died, so the cause can be found again, unless it was another
+    // snake that moved on.
+    let cause = (0..game.snakes.len())
+        .filter(|&player| !game.snakes[player].alive)
+        .find_map(|player| game.collision_cause(player));
+    game.update_status(cause.unwrap_or(DeathCause::OtherSnake));
+}
+
+pub fn encode_json(game: &Game) -> String {
+    serde_json::to_string(&SavedGame::from_game(game)).unwrap()
+}
+
+pub fn decode_json(json: &str) -> Result<Game, SaveError> {
+    #[derive(Deserialize)]
+    struct Version {
+        version: u32,
+    }
+
+    let json_error = |error: serde_json::Error| SaveError::Json(error.to_string());
+    let Version { version } = serde_json::from_str(json).map_err(json_error)?;
+
+    match version {
+        1 => serde_json::from_str::<SavedGameV1>(json)
+            .map_err(json_error)?
+            .into_game(),
+        2 => serde_json::from_str::<SavedGameV2>(json)
+            .map_err(json_error)?
+            .into_game(),
+        3 => serde_json::from_str::<SavedGameV3>(json)
+            .map_err(json_error)?
+            .into_game(),
+        4 => serde_json::from_str::<SavedGameV4>(json)
+            .map_err(json_error)?
+            .into_game(),
+        5 => serde_json::from_str::<SavedGameV5>(json)
+            .map_err(json_error)?
+            .into_game(),
+        6 => serde_json::from_str::<SavedGameV6>(json)
+            .map_err(json_error)?
+            .into_game(),
+        7 => serde_json::from_str::<SavedGameV7>(json)
+            .map_err(json_error)?
+            .into_game(),
+        8 => serde_json::from_str::<SavedGameV8>(json)
+            .map_err(json_error)?
+            .into_game(),
+        9 => serde_json::from_str::<SavedGameV9>(json)
+            .map_err(json_error)?
+            .into_game(),
+        10 => serde_json::from_str::<SavedGameV10>(json)
+            .map_err(json_error)?
+            .into_game(),
+        11 => serde_json::from_str::<SavedGameV11>(json)
+            .map_err(json_error)?
+            .into_game(),
+        SAVE_VERSION => serde_json::from_str::<SavedGame>(json)
+            .map_err(json_error)?
+            .into_game(),
+        _ => Err(SaveError::UnsupportedVersion(version)),
+    }
+}
+
+/// The binary format is the bincode encoding of `SavedGame`; since `version` is its first field,
+/// the first four bytes are always the little-endian version, which is read before the rest.
+pub fn encode_binary(game: &Game) -> Vec<u8> {
+    bincode::serialize(&SavedGame::from_game(game)).unwrap()
+}
+
+pub fn decode_binary(bytes: &[u8]) -> Result<Game, SaveError> {
+    let version = bytes
+        .get(..4)
+        .map(|header| u32::from_le_bytes(header.try_into().unwrap()))
+        .ok_or_else(|| SaveError::Binary("missing version header".to_string()))?;
+
+    let binary_error = |error: bincode::Error| SaveError::Binary(error.to_string());
+
+    match version {
+        1 => bincode::deserialize::<SavedGameV1>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        2 => bincode::deserialize::<SavedGameV2>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        3 => bincode::deserialize::<SavedGameV3>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        4 => bincode::deserialize::<SavedGameV4>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        5 => bincode::deserialize::<SavedGameV5>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        6 => bincode::deserialize::<SavedGameV6>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        7 => bincode::deserialize::<SavedGameV7>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        8 => bincode::deserialize::<SavedGameV8>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        9 => bincode::deserialize::<SavedGameV9>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        10 => bincode::deserialize::<SavedGameV10>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        11 => bincode::deserialize::<SavedGameV11>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        SAVE_VERSION => bincode::deserialize::<SavedGame>(bytes)
+            .map_err(binary_error)?
+            .into_game(),
+        _ => Err(SaveError::UnsupportedVersion(version)),
+    }
+}
@@ -0,0 +1,236 @@
+//! Lifecycle of the games played one after another: starting, pausing and resuming them, and
+//! restarting once they're over.
+//!
+//! The session keeps the time: it's given the current time on each call (e.g. `Date.now()`), and
+//! leaves the paused time out of the elapsed one.
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::config::{ConfigError, GameConfig};
+use crate::level::LevelError;
+use crate::replay::{Recorder, Replay};
+use crate::{Game, GameStatus, Movement, TurnRejection};
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SessionState {
+    /// The game has been created, but its time isn't running yet.
+    Ready,
+    Running,
+    Paused,
+    GameOver,
+    Won,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SessionAction {
+    Start,
+    Pause,
+    Resume,
+    Update,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SessionError {
+    Config(ConfigError),
+    Level(LevelError),
+    InvalidTransition {
+        state: SessionState,
+        action: SessionAction,
+    },
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Config(error) => write!(f, "invalid config: {}", error),
+            SessionError::Level(error) => write!(f, "invalid level: {}", error),
+            SessionError::InvalidTransition { state, action } => {
+                let action = match action {
+                    SessionAction::Start => "start",
+                    SessionAction::Pause => "pause",
+                    SessionAction::Resume => "resume",
+                    SessionAction::Update => "update",
+                };
+                let state = match state {
+                    SessionState::Ready => "ready",
+                    SessionState::Running => "running",
+                    SessionState::Paused => "paused",
+                    SessionState::GameOver => "over",
+                    SessionState::Won => "won",
+                };
+                write!(f, "can't {} a game that's {}", action, state)
+            }
+        }
+    }
+}
+
+/// Plays games with the same settings, recording each of them.
+///
+/// Like `Recorder`, the session doesn't own the game, which is passed to the methods that change
+/// it; it must be the one returned by `create_game()` or by the last `restart()`.
+#[wasm_bindgen]
+pub struct Session {
+    config: GameConfig,
+    level: Option<String>,
+    recorder: Recorder,
+    /// Replay of the game played before the current one, if any.
+    last_replay: Option<Replay>,
+    state: SessionState,
+    /// Time of the last update, or of the start (or resume) if there hasn't been any since.
+    last_update: f64,
+    paused_at: f64,
+}
+
+#[wasm_bindgen]
+impl Session {
+    /// Fails with a readable message if the config or the level are invalid; the level map, if
+    /// given, overrides the board and snake settings as in `Recorder::set_level()`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &GameConfig, level: Option<String>) -> Result<Session, JsValue> {
+        Session::with_config(config, level).map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    /// Creates the current game, as it was before starting.
+    pub fn create_game(&self) -> Game {
+        self.recorder.create_game()
+    }
+
+    /// Lets the time run, from `now` on; the game must be ready.
+    pub fn start(&mut self, now: f64) -> Result<(), JsValue> {
+        self.apply(SessionAction::Start, now)
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Stops the time at `now`; the game must be running.
+    pub fn pause(&mut self, now: f64) -> Result<(), JsValue> {
+        self.apply(SessionAction::Pause, now)
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Lets the time run again, from `now` on; the game must be paused.
+    pub fn resume(&mut self, now: f64) -> Result<(), JsValue> {
+        self.apply(SessionAction::Resume, now)
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Abandons the current game, whatever its state, and returns a new one, ready to start; its
+    /// seed is random, unless the config has one.
+    pub fn restart(&mut self) -> Game {
+        let recorder = Session::create_recorder(&self.config, self.level.as_deref()).unwrap();
+        self.last_replay = Some(std::mem::replace(&mut self.recorder, recorder).get_replay());
+        self.state = SessionState::Ready;
+        self.recorder.create_game()
+    }
+
+    /// Records the turn, as `Recorder::enqueue_turn()`.
+    pub fn enqueue_turn(
+        &mut self,
+        game: &mut Game,
+        player: usize,
+        movement: Movement,
+    ) -> Option<TurnRejection> {
+        self.recorder.enqueue_turn(game, player, movement)
+    }
+
+    /// Advances the game by the time elapsed since the last update, as `Recorder::advance()`, and
+    /// returns the number of fixed steps run; the game must be running, and is over or won
+    /// afterwards, if its status says so.
+    pub fn update(
+        &mut self,
+        game: &mut Game,
+        now: f64,
+        movement: Option<Movement>,
+    ) -> Result<u32, JsValue> {
+        self.try_update(game, now, movement)
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Replay of the current game, so far.
+    pub fn get_replay(&self) -> Replay {
+        self.recorder.get_replay()
+    }
+
+    pub fn last_replay(&self) -> Option<Replay> {
+        self.last_replay.clone()
+    }
+}
+
+impl Session {
+    /// Native equivalent of `new()`.
+    pub fn with_config(
+        config: &GameConfig,
+        level: Option<String>,
+    ) -> Result<Session, SessionError> {
+        let recorder = Session::create_recorder(config, level.as_deref())?;
+        Ok(Session {
+            config: config.clone(),
+            level,
+            recorder,
+            last_replay: None,
+            state: SessionState::Ready,
+            last_update: 0_f64,
+            paused_at: 0_f64,
+        })
+    }
+
+    /// Native equivalent of `start()`, `pause()` and `resume()`.
+    pub fn apply(&mut self, action: SessionAction, now: f64) -> Result<(), SessionError> {
+        match (self.state, action) {
+            (SessionState::Ready, SessionAction::Start) => {
+                self.last_update = now;
+                self.state = SessionState::Running;
+            }
+            (SessionState::Running, SessionAction::Pause) => {
+                self.paused_at = now;
+                self.state = SessionState::Paused;
+            }
+            (SessionState::Paused, SessionAction::Resume) => {
+                // The time spent paused doesn't count as elapsed.
+                self.last_update += now - self.paused_at;
+                self.state = SessionState::Running;
+            }
+            (state, action) => return Err(SessionError::InvalidTransition { state, action }),
+        }
+        Ok(())
+    }
+
+    /// Native equivalent of `update()`.
+    pub fn try_update(
+        &mut self,
+        game: &mut Game,
+        now: f64,
+        movement: Option<Movement>,
+    ) -> Result<u32, SessionError> {
+        if self.state != SessionState::Running {
+            return Err(SessionError::InvalidTransition {
+                state: self.state,
+                action: SessionAction::Update,
+            });
+        }
+        let steps = self
+            .recorder
+            .advance(game, now - self.last_update, movement);
+        self.last_update = now;
+        self.state = match game.status() {
+            GameStatus::Running => SessionState::Running,
+            GameStatus::Won => SessionState::Won,
+            GameStatus::Lost(_) => SessionState::GameOver,
+        };
+        Ok(steps)
+    }
+
+    fn create_recorder(config: &GameConfig, level: Option<&str>) -> Result<Recorder, SessionError> {
+        let mut recorder = Recorder::with_config(config).map_err(SessionError::Config)?;
+        if let Some(level) = level {
+            recorder.try_set_level(level).map_err(SessionError::Level)?;
+        }
+        Ok(recorder)
+    }
+}
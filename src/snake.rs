@@ -0,0 +1,364 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::Side;
+use crate::topology::Bounds;
+use crate::{are_equal, Cell, Movement, Segment, Vector, EPSILON};
+
+/// The points of a snake polyline, from the tail end to the head.
+///
+/// The body only changes at its ends, so the points are kept in a deque, which doesn't allocate
+/// once it's large enough; they're also kept contiguous, so that they can be borrowed as a slice.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Vector>", into = "Vec<Vector>")]
+pub struct SnakeBody {
+    points: VecDeque<Vector>,
+    /// Cached sum of the segment lengths.
+    length: f64,
+}
+
+impl Clone for SnakeBody {
+    fn clone(&self) -> SnakeBody {
+        SnakeBody {
+            points: self.points.clone(),
+            length: self.length,
+        }
+    }
+
+    /// Reuses the allocated points, for the copies made every frame.
+    fn clone_from(&mut self, source: &SnakeBody) {
+        self.points.clone_from(&source.points);
+        self.points.make_contiguous();
+        self.length = source.length;
+    }
+}
+
+impl From<Vec<Vector>> for SnakeBody {
+    fn from(points: Vec<Vector>) -> SnakeBody {
+        let length = points
+            .windows(2)
+            .map(|pair| pair[1].subtract(&pair[0]).length())
+            .sum();
+        SnakeBody {
+            points: points.into(),
+            length,
+        }
+    }
+}
+
+impl From<SnakeBody> for Vec<Vector> {
+    fn from(body: SnakeBody) -> Vec<Vector> {
+        body.points.into()
+    }
+}
+
+impl SnakeBody {
+    pub fn points(&self) -> &[Vector] {
+        self.points.as_slices().0
+    }
+
+    pub fn head(&self) -> Vector {
+        self.points[self.points.len() - 1]
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+
+    /// Moves the tail end forward by the given distance, dropping the points it passes.
+    fn trim_tail(&mut self, distance: f64) {
+        let mut remaining = distance;
+        while self.points.len() > 1 {
+            let segment = Segment::new(&self.points[0], &self.points[1]);
+            let length = segment.length();
+            // If the tail reaches exactly the next point, that point becomes the new tail end,
+            // since a zero-length tail segment has no direction.
+            if are_equal(length, remaining) {
+                self.points.pop_front();
+                self.length -= length;
+                break;
+            } else if length > remaining {
+                let vector = segment.get_vector().normalize().scale_by(remaining);
+                self.points[0] = self.points[0].add(&vector);
+                self.length -= remaining;
+                break;
+            } else {
+                self.points.pop_front();
+                self.length -= length;
+                remaining -= length;
+            }
+        }
+        self.points.make_contiguous();
+    }
+
+    /// Moves the head backward by the given distance, dropping the points it passes; the head
+    /// segment is never dropped.
+    fn trim_head(&mut self, distance: f64) {
+        let mut remaining = distance;
+        while self.points.len() > 2 {
+            let last = self.points.len() - 1;
+            let length = self.points[last].subtract(&self.points[last - 1]).length();
+            if length > remaining {
+                break;
+            }
+            self.points.pop_back();
+            self.length -= length;
+            remaining -= length;
+        }
+        let last = self.points.len() - 1;
+        let segment = Segment::new(&self.points[last], &self.points[last - 1]);
+        let length = segment.length();
+        let retracted = remaining.min(length);
+        if retracted > 0_f64 {
+            let vector = segment.get_vector().normalize().scale_by(retracted);
+            self.points[last] = self.points[last].add(&vector);
+            self.length -= retracted;
+        }
+    }
+
+    /// Moves the head back along the body by the given distance, and the tail end by the given
+    /// one, which is where they were before advancing by them, unless the tail end passed a corner
+    /// in the meantime: the tail end is extended straight.
+    pub(crate) fn rewind(&mut self, distance: f64, tail_distance: f64) {
+        self.extend_tail(tail_distance);
+        self.trim_head(distance);
+    }
+
+    /// Moves the head to the given point, in line with the previous one.
+    fn move_head(&mut self, point: Vector) {
+        let head = self.points.back_mut().unwrap();
+        self.length += point.subtract(head).length();
+        *head = point;
+    }
+
+    fn push_head(&mut self, point: Vector) {
+        self.length += point.subtract(&self.head()).length();
+        self.points.push_back(point);
+        self.points.make_contiguous();
+    }
+
+    /// Moves the tail end backward by the given distance.
+    fn extend_tail(&mut self, distance: f64) {
+        let tail_segment = Segment::new(&self.points[1], &self.points[0]);
+        let vector = tail_segment.get_vector().normalize().scale_by(distance);
+        self.points[0] = self.points[0].add(&vector);
+        self.length += distance;
+    }
+
+    fn translate(&mut self, offset: &Vector) {
+        for point in self.points.iter_mut() {
+            *point = point.add(offset);
+        }
+    }
+}
+
+/// A snake, as a polyline from the tail end to the head.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snake {
+    pub(crate) body: SnakeBody,
+    pub direction: Vector,
+    pub score: i32,
+    pub alive: bool,
+    /// Distance the head has yet to advance while the tail end stays still, after eating. It isn't
+    /// serialized along with the snake, whose format is frozen in the older saves, but saved apart.
+    #[serde(skip)]
+    pub(crate) growth: f64,
+}
+
+impl Snake {
+    pub fn new(head: Vector, direction: Vector, length: i32) -> Snake {
+        let tailtip = head.subtract(&direction.scale_by(f64::from(length)));
+        Snake {
+            body: SnakeBody::from(vec![tailtip, head]),
+            direction,
+            score: 0,
+            alive: true,
+            growth: 0_f64,
+        }
+    }
+
+    pub fn body(&self) -> &[Vector] {
+        self.body.points()
+    }
+
+    pub fn head(&self) -> Vector {
+        self.body.head()
+    }
+
+    /// Moves the snake forward, turning if requested; returns the turning point, and the distance
+    /// travelled to reach it, if the snake turned. The tail end moves by `tail_advance()` of the
+    /// distance.
+    pub(crate) fn advance(
+        &mut self,
+        distance: f64,
+        movement: Option<Movement>,
+    ) -> Option<(Vector, f64)> {
+        let tail_distance = self.tail_advance(distance);
+        self.growth -= distance - tail_distance;
+        self.body.trim_tail(tail_distance);
+        let old_head = self.body.head();
+        let new_head = old_head.add(&self.direction.scale_by(distance));
+        if let Some(movement) = movement {
+            let new_direction = movement.direction();
+            if !self.direction.is_opposite(&new_direction)
+                && !self.direction.equal_to(&new_direction)
+            {
+                let Vector { x: old_x, y: old_y } = old_head;
+                let old_x_rounded = old_x.round();
+                let old_y_rounded = old_y.round();
+                let new_x_rounded = new_head.x.round();
+                let new_y_rounded = new_head.y.round();
+
+                let rounded_x_changed = !are_equal(old_x_rounded, new_x_rounded);
+                let rounded_y_changed = !are_equal(old_y_rounded, new_y_rounded);
+                // A snake that just turned is still on the cell center where it did, which would
+                // count as reaching it again.
+                if (rounded_x_changed || rounded_y_changed) && !self.just_turned() {
+                    let (old, old_rounded, new_rounded) = if rounded_x_changed {
+                        (old_x, old_x_rounded, new_x_rounded)
+                    } else {
+                        (old_y, old_y_rounded, new_y_rounded)
+                    };
+                    let breakpoint_component = old_rounded
+                        + (if new_rounded > old_rounded {
+                            0.5_f64
+                        } else {
+                            -0.5_f64
+                        });
+                    let breakpoint = if rounded_x_changed {
+                        Vector::new(breakpoint_component, old_y)
+                    } else {
+                        Vector::new(old_x, breakpoint_component)
+                    };
+                    let travelled = (old - breakpoint_component).abs();
+                    let vector = new_direction.scale_by(distance - travelled);
+                    let head = breakpoint.add(&vector);
+
+                    self.body.move_head(breakpoint);
+                    self.body.push_head(head);
+                    self.direction = new_direction;
+                    return Some((breakpoint, travelled));
+                }
+            }
+        }
+        self.body.move_head(new_head);
+        None
+    }
+
+    /// Turns on the spot, which must be a cell center; returns whether the movement is a turn, that
+    /// is, neither the current direction nor its opposite.
+    pub(crate) fn turn(&mut self, movement: Movement) -> bool {
+        let direction = movement.direction();
+        if self.direction.is_opposite(&direction) || self.direction.equal_to(&direction) {
+            return false;
+        }
+        let head = self.head();
+        self.body.push_head(head);
+        self.direction = direction;
+        true
+    }
+
+    /// Whether the head segment has zero length, which happens when the snake turns at the end of
+    /// a move.
+    fn just_turned(&self) -> bool {
+        let points = self.body();
+        points.len() > 2 && points[points.len() - 1].equal_to(&points[points.len() - 2])
+    }
+
+    /// Total length of the body.
+    pub fn length(&self) -> f64 {
+        self.body.length()
+    }
+
+    /// Grows by one unit: the tail end stays still while the head advances by it, so that the body
+    /// grows along the path the snake travelled.
+    pub(crate) fn grow(&mut self) {
+        self.growth += 1_f64;
+    }
+
+    /// How far the tail end moves when the head advances by the given distance.
+    pub(crate) fn tail_advance(&self, distance: f64) -> f64 {
+        distance - self.growth.min(distance)
+    }
+
+    /// Shrinks by the given units, cancelling the growth still to come first, and then trimming the
+    /// tail end, leaving at least one unit; returns the distance trimmed.
+    pub(crate) fn shrink(&mut self, units: u32) -> f64 {
+        let cancelled = self.growth.min(f64::from(units));
+        self.growth -= cancelled;
+        let distance = (f64::from(units) - cancelled)
+            .min(self.length() - 1_f64)
+            .max(0_f64);
+        self.body.trim_tail(distance);
+        distance
+    }
+
+    /// The head segment, as it lies on the board.
+    pub(crate) fn head_segments<'a>(
+        &'a self,
+        bounds: &'a Bounds,
+    ) -> impl Iterator<Item = (Vector, Vector)> + 'a {
+        let points = self.body();
+        bounds.segments(&points[points.len() - 2..])
+    }
+
+    /// Moves the snake back on the board, if its head left a toroidal one.
+    pub(crate) fn wrap(&mut self, bounds: &Bounds) {
+        let offset = bounds.wrapping_offset(&self.head());
+        if !offset.equal_to(&Vector::new(0_f64, 0_f64)) {
+            self.body.translate(&offset);
+        }
+    }
+
+    /// The point just ahead of the head, which is in the cell the head is entering, when the head
+    /// is on a cell edge; collisions are checked there, so that they're found as soon as the head
+    /// reaches the edge.
+    pub(crate) fn probe(&self) -> Vector {
+        self.head().add(&self.direction.scale_by(EPSILON))
+    }
+
+    /// The edge the head is crossing, or has crossed, if the board is bounded.
+    pub(crate) fn exit_side(&self, bounds: &Bounds) -> Option<Side> {
+        bounds.exit_side(&self.probe())
+    }
+
+    /// The wall cell the head is entering, or is inside, if any.
+    pub(crate) fn wall_hit(&self, walls: &BTreeSet<Cell>, bounds: &Bounds) -> Option<Cell> {
+        let probe = self.probe();
+        let point = probe.add(&bounds.wrapping_offset(&probe));
+        let cell = (point.x.floor() as i32, point.y.floor() as i32);
+        if walls.contains(&cell) {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    /// Index of the body segment at the given distance from the tail end; at a corner, the one
+    /// starting there.
+    pub(crate) fn segment_at(&self, distance: f64) -> usize {
+        let points = self.body();
+        let mut start = 0_f64;
+        for index in 0..points.len() - 1 {
+            start += points[index + 1].subtract(&points[index]).length();
+            if distance < start - EPSILON {
+                return index;
+            }
+        }
+        points.len() - 2
+    }
+
+    /// Whether the given head touches this snake body.
+    pub(crate) fn is_hit_by(&self, head: &Vector, bounds: &Bounds) -> bool {
+        touches(bounds.segments(self.body()), head)
+    }
+}
+
+fn touches(mut segments: impl Iterator<Item = (Vector, Vector)>, head: &Vector) -> bool {
+    segments.any(|(start, end)| {
+        let segment = Segment::new(&start, &end);
+        let projected = segment.get_projected_point(head);
+        segment.is_point_inside(&projected) && Segment::new(head, &projected).length() < 0.5
+    })
+}
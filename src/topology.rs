@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::events::Side;
+use crate::{are_equal, Vector, EPSILON};
+
+/// What happens when a snake reaches the edge of the board.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Topology {
+    /// The snake dies.
+    #[default]
+    Bounded,
+    /// The snake re-enters from the opposite edge.
+    Toroidal,
+}
+
+/// The board geometry.
+///
+/// On toroidal boards, the snakes are moved back on the board as soon as their head leaves it, so
+/// the rest of the body can lie outside of it; the segments are split at the edges (the "seam")
+/// whenever their on-board position matters.
+#[derive(Copy, Clone)]
+pub(crate) struct Bounds {
+    pub width: i32,
+    pub height: i32,
+    pub topology: Topology,
+}
+
+impl Bounds {
+    /// The edge the point is beyond, if the board is bounded and the point is outside of it.
+    pub fn exit_side(&self, point: &Vector) -> Option<Side> {
+        if self.topology != Topology::Bounded {
+            return None;
+        }
+        if point.x < 0.0 {
+            Some(Side::Left)
+        } else if point.x > f64::from(self.width) {
+            Some(Side::Right)
+        } else if point.y < 0.0 {
+            Some(Side::Top)
+        } else if point.y > f64::from(self.height) {
+            Some(Side::Bottom)
+        } else {
+            None
+        }
+    }
+
+    /// The offset that brings the point back on the board, for toroidal boards.
+    pub fn wrapping_offset(&self, point: &Vector) -> Vector {
+        if self.topology == Topology::Bounded {
+            return Vector::new(0_f64, 0_f64);
+        }
+        let offset = |position: f64, size: i32| {
+            let size = f64::from(size);
+            -(position / size).floor() * size
+        };
+        Vector::new(offset(point.x, self.width), offset(point.y, self.height))
+    }
+
+    /// Distance between two points on the board, taking the shortest way across the seam.
+    pub fn distance(&self, one: &Vector, another: &Vector) -> f64 {
+        let delta = one.subtract(another);
+        if self.topology == Topology::Bounded {
+            return delta.length();
+        }
+        let shortest = |delta: f64, size: i32| {
+            let delta = delta.abs() % f64::from(size);
+            delta.min(f64::from(size) - delta)
+        };
+        shortest(delta.x, self.width).hypot(shortest(delta.y, self.height))
+    }
+
+    /// The segments of the polyline, as they lie on the board.
+    pub fn segments<'a>(
+        &'a self,
+        vectors: &'a [Vector],
+    ) -> impl Iterator<Item = (Vector, Vector)> + 'a {
+        vectors
+            .windows(2)
+            .flat_map(move |pair| self.split_at_seam(pair[0], pair[1]))
+    }
+
+    /// The polyline, as a list of polylines lying on the board.
+    pub fn pieces(&self, vectors: &[Vector]) -> Vec<Vec<Vector>> {
+        let mut pieces: Vec<Vec<Vector>> = Vec::new();
+        for (start, end) in self.segments(vectors) {
+            match pieces.last_mut() {
+                Some(piece) if piece[piece.len() - 1].equal_to(&start) => piece.push(end),
+                _ => pieces.push(vec![start, end]),
+            }
+        }
+        pieces
+    }
+
+    /// Splits an axis-aligned segment into the pieces lying on the board, which are more than one
+    /// only on toroidal boards.
+    pub fn split_at_seam(
+        &self,
+        start: Vector,
+        end: Vector,
+    ) -> impl Iterator<Item = (Vector, Vector)> {
+        let bounded = self.topology == Topology::Bounded;
+        let horizontal = are_equal(start.y, end.y);
+        let (size, delta) = if horizontal {
+            (f64::from(self.width), end.x - start.x)
+        } else {
+            (f64::from(self.height), end.y - start.y)
+        };
+        let mut current = start.add(&self.wrapping_offset(&start));
+        let mut remaining = delta;
+        let component = move |vector: &Vector| if horizontal { vector.x } else { vector.y };
+        let moved = move |vector: &Vector, distance: f64| {
+            if horizontal {
+                Vector::new(vector.x + distance, vector.y)
+            } else {
+                Vector::new(vector.x, vector.y + distance)
+            }
+        };
+        // A backwards segment starting on the seam starts on the far edge.
+        if remaining < 0_f64 && component(&current) < EPSILON {
+            current = moved(&current, size);
+        }
+
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            if bounded {
+                done = true;
+                return Some((start, end));
+            }
+            let target = component(&current) + remaining;
+            if target > -EPSILON && target < size + EPSILON {
+                done = true;
+                return Some((current, moved(&current, remaining)));
+            }
+            let edge = if remaining > 0_f64 { size } else { 0_f64 };
+            let edge_point = moved(&current, edge - component(&current));
+            let piece = (current, edge_point);
+            remaining -= edge - component(&current);
+            current = moved(&edge_point, if remaining > 0_f64 { -size } else { size });
+            Some(piece)
+        })
+    }
+}
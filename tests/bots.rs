@@ -0,0 +1,79 @@
+//! The autopilots steer without panicking, whatever the board, and the Hamiltonian one fills it.
+
+use rust_js_snake_game::bot::{AStarBot, Bot, GreedyBot, HamiltonianBot};
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::level::Level;
+use rust_js_snake_game::topology::Topology;
+use rust_js_snake_game::{Game, GameStatus};
+
+/// Plays until the game is over, or the time runs out.
+fn play(game: &mut Game, bot: &mut dyn Bot, max_frames: u32) {
+    for _ in 0..max_frames {
+        if game.is_over() {
+            return;
+        }
+        let movement = bot.next_move(game);
+        game.process(16_f64, movement);
+    }
+}
+
+#[test]
+fn hamiltonian_fills_board() {
+    for &(width, height) in &[(6, 4), (6, 5), (5, 6), (8, 6), (10, 10)] {
+        for &topology in &[Topology::Bounded, Topology::Toroidal] {
+            let config = GameConfig::new(width, height)
+                .speed(0.03)
+                .snake_length(2)
+                .topology(topology)
+                .seed(u64::from(width as u32 * height as u32));
+            let mut game = Game::with_config(&config).unwrap();
+            let mut bot = HamiltonianBot::for_game(&game).unwrap();
+            play(&mut game, &mut bot, 1_000_000);
+            assert_eq!(
+                game.status(),
+                GameStatus::Won,
+                "{}x{} {:?}",
+                width,
+                height,
+                topology
+            );
+            assert_eq!(game.score(), width * height - 2);
+        }
+    }
+}
+
+#[test]
+fn hamiltonian_unsupported_boards() {
+    let odd = Game::with_config(&GameConfig::new(7, 5)).unwrap();
+    assert!(HamiltonianBot::for_game(&odd).is_none());
+    let level = Level::parse("......\n.#....\n..S.>.\n......").unwrap();
+    let walled = Game::with_level_config(&level, &GameConfig::new(6, 4), 1);
+    assert!(HamiltonianBot::for_game(&walled).is_none());
+
+    // A bot built for another board stops steering, rather than panicking.
+    let mut bot = HamiltonianBot::new(4, 4).unwrap();
+    let mut game = Game::with_config(&GameConfig::new(12, 10).seed(1)).unwrap();
+    assert!(bot.next_move(&game).is_none());
+    game.process(100_f64, None);
+    assert!(bot.next_move(&game).is_none());
+}
+
+#[test]
+fn grid_bots_on_levels() {
+    let level = Level::parse(
+        "\
+..........
+..#.......
+..#.S.>...
+..#....#..
+..........
+....##....",
+    )
+    .unwrap();
+    for bot in [&mut GreedyBot as &mut dyn Bot, &mut AStarBot] {
+        let config = GameConfig::new(10, 6).food_count(2);
+        let mut game = Game::with_level_config(&level, &config, 5);
+        play(&mut game, bot, 20_000);
+        assert!(game.score() > 0);
+    }
+}
@@ -0,0 +1,178 @@
+//! Invalid settings are reported as typed errors, rather than making the game panic.
+
+use rust_js_snake_game::config::{ConfigError, GameConfig, MAX_BOARD_SIDE};
+use rust_js_snake_game::difficulty::{SpeedCurve, SpeedDriver};
+use rust_js_snake_game::food::{BonusRules, FoodKind};
+use rust_js_snake_game::power_up::{PowerUpKind, PowerUpRules};
+use rust_js_snake_game::{Game, Vector};
+
+fn error(config: GameConfig) -> ConfigError {
+    config.validate().unwrap_err()
+}
+
+fn power_ups() -> PowerUpRules {
+    PowerUpRules::new(100_f64, 200_f64, 300_f64).kind(&PowerUpKind::ghost(500_f64, 1_f64))
+}
+
+#[test]
+fn valid() {
+    let config = GameConfig::new(20, 20)
+        .players(4)
+        .food_count(3)
+        .food_kind(&FoodKind::new(1, 1, Some(1000_f64), 1_f64))
+        .bonus(&BonusRules::new(100_f64, 200_f64, 300_f64, 5, 1))
+        .power_ups(&power_ups());
+    assert!(Game::with_config(&config).is_ok());
+}
+
+#[test]
+fn board() {
+    assert_eq!(
+        error(GameConfig::new(0, 5)),
+        ConfigError::EmptyBoard {
+            width: 0,
+            height: 5
+        }
+    );
+    assert_eq!(
+        error(GameConfig::new(10, MAX_BOARD_SIDE + 1)),
+        ConfigError::BoardTooLarge {
+            width: 10,
+            height: MAX_BOARD_SIDE + 1
+        }
+    );
+}
+
+#[test]
+fn speed() {
+    for &speed in &[0_f64, -1_f64, f64::INFINITY] {
+        assert_eq!(
+            error(GameConfig::new(10, 10).speed(speed)),
+            ConfigError::InvalidSpeed(speed)
+        );
+    }
+    assert!(matches!(
+        error(GameConfig::new(10, 10).speed(f64::NAN)),
+        ConfigError::InvalidSpeed(speed) if speed.is_nan()
+    ));
+    let curves = [
+        SpeedCurve::linear(SpeedDriver::Score, 0_f64, 0.001),
+        SpeedCurve::linear(SpeedDriver::Time, 100_f64, -0.001),
+        SpeedCurve::stepped(SpeedDriver::Score, 5_f64, 0.001).capped(0.001),
+    ];
+    for curve in &curves {
+        assert_eq!(
+            error(GameConfig::new(10, 10).speed(0.01).speed_curve(curve)),
+            ConfigError::InvalidSpeedCurve
+        );
+    }
+}
+
+#[test]
+fn snakes() {
+    assert_eq!(
+        error(GameConfig::new(10, 10).direction(Vector::new(1_f64, 1_f64))),
+        ConfigError::InvalidDirection { x: 1_f64, y: 1_f64 }
+    );
+    assert_eq!(
+        error(GameConfig::new(10, 10).snake_length(0)),
+        ConfigError::InvalidSnakeLength(0)
+    );
+    assert_eq!(
+        error(GameConfig::new(10, 10).snake_length(6)),
+        ConfigError::SnakeTooLong { length: 6, max: 4 }
+    );
+    assert_eq!(
+        error(GameConfig::new(10, 10).players(0)),
+        ConfigError::NoPlayers
+    );
+    assert_eq!(
+        error(GameConfig::new(10, 10).players(11)),
+        ConfigError::TooManyPlayers(11)
+    );
+    // Without building the positions of all the snakes first.
+    assert_eq!(
+        error(GameConfig::new(10, 10).players(usize::MAX)),
+        ConfigError::TooManyPlayers(usize::MAX)
+    );
+}
+
+#[test]
+fn food() {
+    assert_eq!(
+        error(GameConfig::new(10, 10).food_count(0)),
+        ConfigError::NoFood
+    );
+    assert_eq!(
+        error(
+            GameConfig::new(10, 10)
+                .food_kind(&FoodKind::default())
+                .food_kind(&FoodKind::new(1, 1, None, 0_f64))
+        ),
+        ConfigError::InvalidFoodWeight {
+            kind: 1,
+            weight: 0_f64
+        }
+    );
+    assert_eq!(
+        error(GameConfig::new(10, 10).food_kind(&FoodKind::new(1, 1, Some(-1_f64), 1_f64))),
+        ConfigError::InvalidFoodLifetime {
+            kind: 0,
+            lifetime: -1_f64
+        }
+    );
+}
+
+#[test]
+fn bonus() {
+    let bonus = |min, max, lifetime, score| {
+        error(GameConfig::new(10, 10).bonus(&BonusRules::new(min, max, lifetime, score, 1)))
+    };
+    assert_eq!(
+        bonus(200_f64, 100_f64, 300_f64, 5),
+        ConfigError::InvalidBonusInterval {
+            min: 200_f64,
+            max: 100_f64
+        }
+    );
+    assert_eq!(
+        bonus(100_f64, 200_f64, 0_f64, 5),
+        ConfigError::InvalidBonusLifetime(0_f64)
+    );
+    assert_eq!(
+        bonus(100_f64, 200_f64, 300_f64, 0),
+        ConfigError::InvalidBonusScore(0)
+    );
+}
+
+#[test]
+fn power_up_rules() {
+    let error_of = |power_ups: PowerUpRules| error(GameConfig::new(10, 10).power_ups(&power_ups));
+    assert_eq!(
+        error_of(PowerUpRules::new(100_f64, 200_f64, 300_f64)),
+        ConfigError::NoPowerUpKinds
+    );
+    assert_eq!(
+        error_of(
+            PowerUpRules::new(-1_f64, 200_f64, 300_f64).kind(&PowerUpKind::ghost(500_f64, 1_f64))
+        ),
+        ConfigError::InvalidPowerUpInterval {
+            min: -1_f64,
+            max: 200_f64
+        }
+    );
+    assert_eq!(
+        error_of(
+            PowerUpRules::new(100_f64, 200_f64, 0_f64).kind(&PowerUpKind::ghost(500_f64, 1_f64))
+        ),
+        ConfigError::InvalidPowerUpLifetime(0_f64)
+    );
+    assert_eq!(
+        error_of(power_ups().kind(&PowerUpKind::speed(0_f64, 500_f64, 1_f64))),
+        ConfigError::InvalidPowerUpKind(1)
+    );
+    assert_eq!(
+        error_of(power_ups().kind(&PowerUpKind::ghost(-1_f64, 1_f64))),
+        ConfigError::InvalidPowerUpKind(1)
+    );
+}
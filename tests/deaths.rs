@@ -0,0 +1,104 @@
+//! Each death is reported with its cause, and the one that ends the game is the loss cause.
+
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::events::{DeathCause, DeathCauseKind, GameEvent, Side};
+use rust_js_snake_game::level::Level;
+use rust_js_snake_game::{Game, Movement, Vector};
+
+/// Queues the turns, then plays until the game is over; returns the deaths, as `(player, cause)`.
+fn play(game: &mut Game, turns: &[(usize, Movement)]) -> Vec<(usize, DeathCause)> {
+    for &(player, movement) in turns {
+        assert!(game.push_turn(player, movement).is_ok());
+    }
+    let mut deaths = Vec::new();
+    for _ in 0..1000 {
+        if game.is_over() {
+            break;
+        }
+        game.process(16_f64, None);
+        deaths.extend(
+            game.drain_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    GameEvent::Died { player, cause, .. } => Some((player, cause)),
+                    _ => None,
+                }),
+        );
+    }
+    assert!(game.is_over());
+    deaths
+}
+
+#[test]
+fn edges() {
+    let sides = [
+        (Movement::TOP, Side::Top),
+        (Movement::RIGHT, Side::Right),
+        (Movement::DOWN, Side::Bottom),
+        (Movement::LEFT, Side::Left),
+    ];
+    for &(movement, side) in &sides {
+        let config = GameConfig::new(10, 8).direction(movement.direction());
+        let mut game = Game::with_config(&config).unwrap();
+        let cause = DeathCause::Wall { side };
+        assert_eq!(play(&mut game, &[]), vec![(0, cause)]);
+        assert_eq!(game.death_cause(), Some(cause));
+        assert_eq!(game.loss_cause(), Some(DeathCauseKind::Wall));
+    }
+}
+
+#[test]
+fn obstacle() {
+    let level = Level::parse("S.>..#\n......").unwrap();
+    let mut game = Game::with_level_config(&level, &GameConfig::new(6, 2), 1);
+    let cause = DeathCause::Obstacle { cell: (5, 0) };
+    assert_eq!(play(&mut game, &[]), vec![(0, cause)]);
+    assert_eq!(game.loss_cause(), Some(DeathCauseKind::Obstacle));
+}
+
+#[test]
+fn self_collision() {
+    let config = GameConfig::new(20, 8).snake_length(5);
+    let mut game = Game::with_config(&config).unwrap();
+    let turns = [(0, Movement::DOWN), (0, Movement::LEFT), (0, Movement::TOP)];
+    let deaths = play(&mut game, &turns);
+    // The head, going up, bites the tail segment, which lies on the row it turned from.
+    let head = game.head();
+    assert_eq!(
+        deaths,
+        vec![(
+            0,
+            DeathCause::SelfCollision {
+                segment_index: 0,
+                point: head,
+            }
+        )]
+    );
+    let body = game.snakes()[0].body();
+    assert_eq!(body[0].y, body[1].y);
+    assert!((head.y - body[0].y - 0.5_f64).abs() < 1e-9);
+    assert_eq!(game.loss_cause(), Some(DeathCauseKind::SelfCollision));
+}
+
+#[test]
+fn other_snake() {
+    // The snakes start on the rows 1 and 3, side by side.
+    let config = GameConfig::new(10, 6).players(2);
+    let mut game = Game::with_config(&config).unwrap();
+    assert_eq!(game.snakes()[0].head(), Vector::new(4.5, 1.5));
+    assert_eq!(game.snakes()[1].head(), Vector::new(4.5, 3.5));
+
+    let mut head_on = Game::with_config(&config).unwrap();
+    let deaths = play(&mut head_on, &[(0, Movement::DOWN), (1, Movement::TOP)]);
+    assert_eq!(
+        deaths,
+        vec![(0, DeathCause::HeadToHead), (1, DeathCause::HeadToHead)]
+    );
+    assert_eq!(head_on.loss_cause(), Some(DeathCauseKind::HeadToHead));
+
+    // Player 0 runs into the body of player 1, which goes on.
+    let deaths = play(&mut game, &[(0, Movement::DOWN)]);
+    assert_eq!(deaths, vec![(0, DeathCause::OtherSnake)]);
+    assert!(game.snakes()[1].alive);
+    assert_eq!(game.loss_cause(), Some(DeathCauseKind::OtherSnake));
+}
@@ -0,0 +1,68 @@
+//! The random decisions of a game all come from its seed, so that the same inputs always produce
+//! the same game.
+
+use rust_js_snake_game::bot::{Bot, GreedyBot};
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::food::{BonusRules, FoodKind};
+use rust_js_snake_game::power_up::{PowerUpKind, PowerUpRules};
+use rust_js_snake_game::save;
+use rust_js_snake_game::topology::Topology;
+use rust_js_snake_game::Game;
+
+/// A game with all the random items: food of several kinds, the bonus and power-ups.
+fn config(seed: u64) -> GameConfig {
+    let power_ups = PowerUpRules::new(400_f64, 1000_f64, 2000_f64)
+        .kind(&PowerUpKind::speed(1.5, 700_f64, 1_f64))
+        .kind(&PowerUpKind::ghost(900_f64, 1_f64))
+        .kind(&PowerUpKind::shrink(1, 1_f64));
+    GameConfig::new(15, 11)
+        .topology(Topology::Toroidal)
+        .food_count(2)
+        .food_kind(&FoodKind::new(1, 1, Some(2500_f64), 1_f64))
+        .food_kind(&FoodKind::new(3, 2, None, 0.5))
+        .bonus(&BonusRules::new(600_f64, 1800_f64, 1500_f64, 5, 1))
+        .power_ups(&power_ups)
+        .seed(seed)
+}
+
+/// Plays the given number of frames of uneven length, steered by a bot.
+fn play(game: &mut Game, frames: u32) {
+    for frame in 0..frames {
+        if let Some(movement) = GreedyBot.next_move(game) {
+            let _ = game.push_turn(0, movement);
+        }
+        game.process(f64::from(7 + frame % 19), None);
+    }
+}
+
+#[test]
+fn seeded_games_are_deterministic() {
+    let mut first = Game::with_config(&config(5)).unwrap();
+    let mut second = Game::with_config(&config(5)).unwrap();
+    assert_eq!(save::encode_json(&first), save::encode_json(&second));
+    for _ in 0..40 {
+        play(&mut first, 25);
+        play(&mut second, 25);
+        assert_eq!(save::encode_json(&first), save::encode_json(&second));
+    }
+
+    // The seed is what picks the food.
+    let mut other = Game::with_config(&config(6)).unwrap();
+    play(&mut other, 1000);
+    assert_ne!(save::encode_json(&other), save::encode_json(&first));
+}
+
+#[test]
+fn seeded_food_placement() {
+    let foods = |seed: u64| {
+        let game = Game::with_config(&GameConfig::new(20, 20).food_count(5).seed(seed)).unwrap();
+        let foods: Vec<(f64, f64)> = game
+            .foods()
+            .iter()
+            .map(|food| (food.position.x, food.position.y))
+            .collect();
+        foods
+    };
+    assert_eq!(foods(8), foods(8));
+    assert_ne!(foods(8), foods(9));
+}
@@ -0,0 +1,232 @@
+//! The environments move the snake a cell per step, and reward the outcome of each of them.
+
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::env::{BatchEnv, Channel, Env, EnvConfig, Rewards};
+use rust_js_snake_game::events::{DeathCause, Side};
+use rust_js_snake_game::Movement;
+
+fn config() -> EnvConfig {
+    EnvConfig::new(GameConfig::new(10, 8))
+}
+
+/// Varied actions, the same for a given environment and step.
+fn action(index: usize, step: usize) -> Movement {
+    let movements = [
+        Movement::TOP,
+        Movement::RIGHT,
+        Movement::DOWN,
+        Movement::LEFT,
+    ];
+    movements[(index * 7 + step * 3 + step / 5) % 4]
+}
+
+fn cell(env: &Env) -> (i32, i32) {
+    let head = env.game().head();
+    (head.x.floor() as i32, head.y.floor() as i32)
+}
+
+/// The direction toward the food, along the axis where it's farther.
+fn toward_food(env: &Env) -> Movement {
+    let head = env.game().head();
+    let food = env.game().foods()[0].position;
+    let (delta_x, delta_y) = (food.x - head.x, food.y - head.y);
+    if delta_x.abs() > delta_y.abs() {
+        if delta_x > 0_f64 {
+            Movement::RIGHT
+        } else {
+            Movement::LEFT
+        }
+    } else if delta_y > 0_f64 {
+        Movement::DOWN
+    } else {
+        Movement::TOP
+    }
+}
+
+#[test]
+fn reset() {
+    let mut env = Env::new(config()).unwrap();
+    let observation = env.reset(3);
+    assert_eq!(
+        (observation.channels, observation.height, observation.width),
+        (4, 8, 10)
+    );
+    assert_eq!(observation.data.len(), env.observation_size());
+    let count = |channel: usize| {
+        observation.data[channel * 80..][..80]
+            .iter()
+            .filter(|&&value| value == 1_f32)
+            .count()
+    };
+    // Head, body of length 3 spanning 4 cell centers, food, and no walls.
+    assert_eq!(count(0), 1);
+    assert_eq!(observation.get(0, cell(&env)), 1_f32);
+    assert_eq!(count(1), 4);
+    assert_eq!(count(2), 1);
+    assert_eq!(count(3), 0);
+
+    env.step(Movement::DOWN);
+    assert_eq!(env.reset(3).data, observation.data);
+}
+
+#[test]
+fn channels() {
+    let config = config().channels(vec![Channel::Food, Channel::Head]);
+    let mut env = Env::new(config).unwrap();
+    let observation = env.reset(0);
+    assert_eq!(observation.channels, 2);
+    assert_eq!(observation.get(1, cell(&env)), 1_f32);
+    assert_eq!(observation.data.iter().sum::<f32>(), 2_f32);
+}
+
+#[test]
+fn steps() {
+    let mut env = Env::new(config()).unwrap();
+    env.reset(0);
+    let (x, y) = cell(&env);
+    // Reversing is ignored.
+    let (observation, reward, done, info) = env.step(Movement::LEFT);
+    assert_eq!(cell(&env), (x + 1, y));
+    assert_eq!(observation.get(0, (x + 1, y)), 1_f32);
+    assert_eq!((reward, done), (0_f64, false));
+    assert_eq!((info.steps, info.score, info.death), (1, 0, None));
+    env.step(Movement::DOWN);
+    assert_eq!(cell(&env), (x + 1, y + 1));
+}
+
+#[test]
+fn food() {
+    let rewards = Rewards {
+        food: 2_f64,
+        step: -0.5_f64,
+        ..Rewards::default()
+    };
+    let mut env = Env::new(EnvConfig::new(GameConfig::new(20, 20)).rewards(rewards)).unwrap();
+    env.reset(5);
+    for _ in 0..40 {
+        let (_, reward, done, info) = env.step(toward_food(&env));
+        assert!(!done);
+        if info.score > 0 {
+            assert_eq!(info.score, 1);
+            assert_eq!(reward, 1.5_f64);
+            return;
+        }
+        assert_eq!(reward, -0.5_f64);
+    }
+    panic!("the snake should have reached the food");
+}
+
+#[test]
+fn death() {
+    let mut env = Env::new(config()).unwrap();
+    env.reset(0);
+    let mut last = None;
+    for _ in 0..10 {
+        last = Some(env.step(Movement::RIGHT));
+        if last.as_ref().unwrap().2 {
+            break;
+        }
+    }
+    let (_, reward, done, info) = last.unwrap();
+    assert!(done);
+    assert_eq!(reward, -1_f64);
+    assert_eq!(info.death, Some(DeathCause::Wall { side: Side::Right }));
+    assert!(!info.truncated);
+
+    // Once over, steps do nothing.
+    let (_, reward, done, after) = env.step(Movement::DOWN);
+    assert_eq!((reward, done), (0_f64, true));
+    assert_eq!(after, info);
+
+    env.reset(0);
+    assert_eq!(env.step(Movement::DOWN).3.death, None);
+}
+
+#[test]
+fn truncation() {
+    let mut env = Env::new(config().max_steps(2)).unwrap();
+    env.reset(0);
+    let (_, _, done, info) = env.step(Movement::DOWN);
+    assert!(!done && !info.truncated);
+    let (_, _, done, info) = env.step(Movement::RIGHT);
+    assert!(done && info.truncated);
+    assert_eq!(info.steps, 2);
+}
+
+#[test]
+fn invalid_config() {
+    assert!(Env::new(EnvConfig::new(GameConfig::new(0, 8))).is_err());
+}
+
+#[test]
+fn batch_matches_envs() {
+    let size = 6;
+    let mut batch = BatchEnv::new(config().max_steps(30), size, 1).unwrap();
+    let mut envs: Vec<Env> = (0..size)
+        .map(|_| Env::new(config().max_steps(30)).unwrap())
+        .collect();
+    let observation_size = batch.observation_size();
+    let observations = batch.reset(10).to_vec();
+    for (index, env) in envs.iter_mut().enumerate() {
+        let observation = env.reset(10 + index as u64);
+        assert_eq!(
+            observation.data,
+            observations[index * observation_size..][..observation_size]
+        );
+    }
+
+    // Finished environments restart with the following seeds, in order.
+    let mut next_seed = 10 + size as u64;
+    let mut episodes = 0;
+    for step in 0..100 {
+        let actions: Vec<Movement> = (0..size).map(|index| action(index, step)).collect();
+        let batch_step = batch.step(&actions);
+        for (index, env) in envs.iter_mut().enumerate() {
+            let (mut observation, reward, done, info) = env.step(actions[index]);
+            if done {
+                observation = env.reset(next_seed);
+                next_seed += 1;
+                episodes += 1;
+            }
+            assert_eq!(batch_step.rewards[index], reward);
+            assert_eq!(batch_step.dones[index], done);
+            assert_eq!(batch_step.infos[index], info);
+            assert_eq!(
+                observation.data,
+                batch_step.observations[index * observation_size..][..observation_size]
+            );
+        }
+    }
+    assert!(episodes > size);
+}
+
+#[test]
+fn batch_threads() {
+    // Enough environments for several threads.
+    let size = 700;
+    let mut single = BatchEnv::new(config(), size, 1).unwrap();
+    let mut multiple = BatchEnv::new(config(), size, 3).unwrap();
+    assert_eq!(single.reset(1), multiple.reset(1));
+    for step in 0..40 {
+        let actions: Vec<Movement> = (0..size).map(|index| action(index, step)).collect();
+        let one = single.step(&actions);
+        let (observations, rewards, dones, infos) = (
+            one.observations.to_vec(),
+            one.rewards.to_vec(),
+            one.dones.to_vec(),
+            one.infos.to_vec(),
+        );
+        let other = multiple.step(&actions);
+        assert_eq!(other.observations, &observations[..]);
+        assert_eq!(other.rewards, &rewards[..]);
+        assert_eq!(other.dones, &dones[..]);
+        assert_eq!(other.infos, &infos[..]);
+    }
+}
+
+#[test]
+#[should_panic(expected = "one action per environment")]
+fn batch_actions() {
+    let mut batch = BatchEnv::new(config(), 2, 1).unwrap();
+    batch.step(&[Movement::DOWN]);
+}
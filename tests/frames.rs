@@ -0,0 +1,156 @@
+//! The outcome of a game mustn't depend on how its time is split into frames.
+
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::difficulty::{SpeedCurve, SpeedDriver};
+use rust_js_snake_game::food::{BonusRules, FoodKind};
+use rust_js_snake_game::power_up::{PowerUpKind, PowerUpRules};
+use rust_js_snake_game::save;
+use rust_js_snake_game::topology::Topology;
+use rust_js_snake_game::{Game, Movement};
+
+/// Turns queued at the given milliseconds, which are multiples of all the frame lengths, and fall
+/// between the times the head reaches a cell center.
+const TURNS: &[(f64, Movement)] = &[
+    (1050_f64, Movement::DOWN),
+    (1550_f64, Movement::LEFT),
+    (3050_f64, Movement::TOP),
+    (4550_f64, Movement::RIGHT),
+    (6050_f64, Movement::DOWN),
+    (8050_f64, Movement::LEFT),
+];
+
+/// Plays the given milliseconds in frames of the given length.
+fn play(config: &GameConfig, frame: f64, turns: &[(f64, Movement)], duration: f64) -> Game {
+    let mut game = Game::with_config(config).unwrap();
+    let frames = (duration / frame).round() as u64;
+    for index in 0..frames {
+        let time = index as f64 * frame;
+        for &(_, turn) in turns
+            .iter()
+            .filter(|(at, _)| (at - time).abs() < frame / 2_f64)
+        {
+            let _ = game.push_turn(0, turn);
+        }
+        game.process(frame, None);
+    }
+    game
+}
+
+/// The state of the game, with the positions rounded, since the rounding errors depend on the
+/// frames.
+fn summary(game: &Game) -> String {
+    let round = |value: f64| format!("{:.6}", value + 0_f64);
+    let snakes: Vec<String> = game
+        .snakes()
+        .iter()
+        .map(|snake| {
+            let body: Vec<String> = snake
+                .body()
+                .iter()
+                .map(|point| format!("({}, {})", round(point.x), round(point.y)))
+                .collect();
+            format!("{} {} [{}]", snake.score, snake.alive, body.join(" "))
+        })
+        .collect();
+    let foods: Vec<String> = game
+        .foods()
+        .iter()
+        .map(|food| format!("({}, {})", food.position.x, food.position.y))
+        .collect();
+    let bonus = game.bonus().map(|bonus| bonus.position);
+    format!(
+        "{:?}; snakes: {}; foods: {}; bonus: {:?}",
+        game.status(),
+        snakes.join(", "),
+        foods.join(" "),
+        bonus
+    )
+}
+
+fn assert_frame_independent(config: &GameConfig, turns: &[(f64, Movement)], duration: f64) {
+    let expected = summary(&play(config, 1_f64, turns, duration));
+    for &frame in &[5_f64, 10_f64, 25_f64, 50_f64] {
+        assert_eq!(
+            summary(&play(config, frame, turns, duration)),
+            expected,
+            "frame: {}",
+            frame
+        );
+    }
+}
+
+fn bonus_config() -> GameConfig {
+    GameConfig::new(12, 9)
+        .topology(Topology::Toroidal)
+        .food_count(3)
+        .bonus(&BonusRules::new(500_f64, 1500_f64, 800_f64, 5, 1))
+        .seed(7)
+}
+
+#[test]
+fn bonus_without_input() {
+    assert_frame_independent(&bonus_config(), &[], 12000_f64);
+}
+
+#[test]
+fn bonus_with_turns() {
+    assert_frame_independent(&bonus_config(), TURNS, 12000_f64);
+}
+
+#[test]
+fn expiring_food() {
+    let config = GameConfig::new(12, 9)
+        .topology(Topology::Toroidal)
+        .food_count(2)
+        .food_kind(&FoodKind::new(1, 1, Some(700_f64), 1_f64))
+        .food_kind(&FoodKind::new(3, 2, Some(450_f64), 1_f64))
+        .seed(3);
+    assert_frame_independent(&config, TURNS, 10000_f64);
+}
+
+#[test]
+fn power_ups() {
+    let rules = PowerUpRules::new(300_f64, 900_f64, 700_f64)
+        .kind(&PowerUpKind::speed(1.5, 600_f64, 1_f64))
+        .kind(&PowerUpKind::speed(0.6, 400_f64, 1_f64))
+        .kind(&PowerUpKind::shrink(1, 1_f64))
+        .kind(&PowerUpKind::score_multiplier(2, 900_f64, 1_f64));
+    let config = GameConfig::new(12, 9)
+        .topology(Topology::Toroidal)
+        .food_count(3)
+        .power_ups(&rules)
+        .seed(11);
+    assert_frame_independent(&config, TURNS, 12000_f64);
+}
+
+/// The snake eats twice, and dies against the top edge.
+#[test]
+fn bounded_board() {
+    let config = GameConfig::new(16, 9).food_count(3).seed(6);
+    assert_frame_independent(&config, TURNS, 12000_f64);
+}
+
+#[test]
+fn speed_curve() {
+    let curve = SpeedCurve::linear(SpeedDriver::Time, 1000_f64, 0.001);
+    let config = GameConfig::new(12, 9)
+        .topology(Topology::Toroidal)
+        .speed_curve(&curve)
+        .food_count(3)
+        .seed(9);
+    assert_frame_independent(&config, TURNS, 10000_f64);
+}
+
+/// Frames without a positive, finite length are ignored, rather than never ending.
+#[test]
+fn invalid_timespans() {
+    let config = GameConfig::new(12, 9).topology(Topology::Toroidal).seed(4);
+    let mut game = Game::with_config(&config).unwrap();
+    game.process(100_f64, None);
+    let state = save::encode_json(&game);
+    for &timespan in &[f64::INFINITY, f64::NEG_INFINITY, f64::NAN, -50_f64, 0_f64] {
+        game.process(timespan, None);
+        assert_eq!(game.advance(timespan, None), 0);
+        assert_eq!(save::encode_json(&game), state, "{}", timespan);
+    }
+}
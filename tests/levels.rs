@@ -0,0 +1,66 @@
+//! Level parse errors point to the line and column that caused them.
+
+use rust_js_snake_game::config::MAX_BOARD_SIDE;
+use rust_js_snake_game::level::{Level, LevelError, LevelErrorKind};
+
+fn error(source: &str) -> LevelError {
+    match Level::parse(source) {
+        Ok(_) => panic!("{:?} should not parse", source),
+        Err(error) => error,
+    }
+}
+
+fn at(line: usize, column: usize, kind: LevelErrorKind) -> LevelError {
+    LevelError { line, column, kind }
+}
+
+#[test]
+fn valid() {
+    let level = Level::parse("#####\n#S.>#\n#...#\n").unwrap();
+    assert_eq!((level.width, level.height), (5, 3));
+    assert_eq!((level.start, level.head), ((1, 1), (3, 1)));
+    assert_eq!(level.snake_length(), 2);
+    assert_eq!(level.walls.len(), 9);
+}
+
+#[test]
+fn size() {
+    assert_eq!(error(""), at(1, 1, LevelErrorKind::Empty));
+    assert_eq!(error("\n  \n"), at(1, 1, LevelErrorKind::Empty));
+
+    let side = MAX_BOARD_SIDE as usize;
+    let wide = format!("S>{}", ".".repeat(side - 1));
+    assert_eq!(error(&wide), at(1, side + 1, LevelErrorKind::TooLarge));
+    let tall = format!("S>\n{}", "..\n".repeat(side));
+    assert_eq!(error(&tall), at(side + 1, 1, LevelErrorKind::TooLarge));
+}
+
+#[test]
+fn rows() {
+    let uneven = |expected, found| LevelErrorKind::UnevenRow { expected, found };
+    assert_eq!(error("S.>\n.."), at(2, 3, uneven(3, 2)));
+    assert_eq!(error("S.>\n...\n...."), at(3, 4, uneven(3, 4)));
+    assert_eq!(
+        error("S.>\n.x."),
+        at(2, 2, LevelErrorKind::UnknownCharacter('x'))
+    );
+}
+
+#[test]
+fn snake() {
+    assert_eq!(error("S.>\nS.."), at(2, 1, LevelErrorKind::DuplicateStart));
+    assert_eq!(error("S>>"), at(1, 3, LevelErrorKind::DuplicateHead));
+    assert_eq!(error("..>\n..."), at(3, 1, LevelErrorKind::MissingStart));
+    assert_eq!(error("S..\n..."), at(3, 1, LevelErrorKind::MissingHead));
+    assert_eq!(error("S..\n..>"), at(2, 3, LevelErrorKind::MisalignedHead));
+    assert_eq!(error(">.S"), at(1, 1, LevelErrorKind::MisalignedHead));
+    assert_eq!(error("S#>"), at(1, 3, LevelErrorKind::BlockedSnake));
+}
+
+#[test]
+fn message() {
+    assert_eq!(
+        error("S.>\n.x.").to_string(),
+        "line 2, column 2: unknown character 'x'"
+    );
+}
@@ -0,0 +1,95 @@
+//! Effects defined outside the crate work like the built-in ones, once registered.
+
+use serde::{Deserialize, Serialize};
+
+use rust_js_snake_game::bot::{Bot, GreedyBot};
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::power_up::{self, Effect, PowerUpKind, PowerUpRules, Stacking};
+use rust_js_snake_game::save;
+use rust_js_snake_game::Game;
+
+/// Slows the game down, and makes the food worth more.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Feast {
+    score_factor: i32,
+}
+
+impl Effect for Feast {
+    fn kind(&self) -> &'static str {
+        "Feast"
+    }
+
+    fn name(&self) -> &'static str {
+        "feast"
+    }
+
+    fn stacking(&self) -> Stacking {
+        Stacking::Extend
+    }
+
+    fn speed_factor(&self, _stacks: u32) -> f64 {
+        0.5
+    }
+
+    fn score_factor(&self, _stacks: u32) -> i32 {
+        self.score_factor
+    }
+
+    fn is_valid(&self) -> bool {
+        self.score_factor > 0
+    }
+}
+
+fn config(score_factor: i32) -> GameConfig {
+    let kind = PowerUpKind::new(Box::new(Feast { score_factor }), 5000_f64, 1_f64);
+    GameConfig::new(12, 10)
+        .food_count(3)
+        .power_ups(&PowerUpRules::new(100_f64, 300_f64, 4000_f64).kind(&kind))
+        .seed(3)
+}
+
+#[test]
+fn custom_effect() {
+    power_up::register_effect::<Feast>("Feast");
+    let mut game = Game::with_config(&config(3)).unwrap();
+    let mut collected = false;
+    for _ in 0..3000 {
+        if game.is_over() {
+            break;
+        }
+        if let Some(movement) = GreedyBot.next_move(&game) {
+            let _ = game.push_turn(0, movement);
+        }
+        game.process(16_f64, None);
+        collected |= !game.active_effects().is_empty();
+
+        let json = save::encode_json(&game);
+        assert_eq!(save::encode_json(&save::decode_json(&json).unwrap()), json);
+        let bytes = save::encode_binary(&game);
+        assert_eq!(
+            save::encode_binary(&save::decode_binary(&bytes).unwrap()),
+            bytes
+        );
+    }
+    assert!(collected);
+    assert!(save::encode_json(&game).contains("\"Feast\":{\"score_factor\":3}"));
+}
+
+#[test]
+fn custom_effect_validation() {
+    power_up::register_effect::<Feast>("Feast");
+    assert!(config(0).validate().is_err());
+    let kind = PowerUpKind::custom("Feast", "{\"score_factor\":2}", 5000_f64, 1_f64).unwrap();
+    assert_eq!(
+        kind,
+        PowerUpKind::new(Box::new(Feast { score_factor: 2 }), 5000_f64, 1_f64)
+    );
+    assert_eq!(kind.name(), "feast");
+}
+
+#[test]
+fn unregistered_effect() {
+    let game = Game::with_config(&config(2)).unwrap();
+    let json = save::encode_json(&game).replace("\"Feast\"", "\"Famine\"");
+    assert!(save::decode_json(&json).is_err());
+}
@@ -0,0 +1,130 @@
+//! A replay records what determines a game: its settings, its seed and its inputs.
+
+use rust_js_snake_game::bot::{Bot, GreedyBot};
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::food::{BonusRules, FoodKind};
+use rust_js_snake_game::power_up::{PowerUpKind, PowerUpRules};
+use rust_js_snake_game::replay::{Recorder, Replay, MIN_REPLAY_VERSION, REPLAY_VERSION};
+use rust_js_snake_game::save;
+use rust_js_snake_game::topology::Topology;
+use rust_js_snake_game::{Game, Movement};
+
+const LEVEL: &str = "\
+............
+.#........#.
+.#..S..>..#.
+.#........#.
+............
+.....##.....
+............
+............";
+
+fn config(seed: u64) -> GameConfig {
+    let power_ups = PowerUpRules::new(400_f64, 1000_f64, 2000_f64)
+        .kind(&PowerUpKind::speed(1.5, 700_f64, 1_f64))
+        .kind(&PowerUpKind::ghost(900_f64, 1_f64))
+        .kind(&PowerUpKind::shrink(1, 1_f64));
+    GameConfig::new(15, 11)
+        .topology(Topology::Toroidal)
+        .food_count(2)
+        .food_kind(&FoodKind::new(1, 1, Some(2500_f64), 1_f64))
+        .food_kind(&FoodKind::new(3, 2, None, 0.5))
+        .bonus(&BonusRules::new(600_f64, 1800_f64, 1500_f64, 5, 1))
+        .power_ups(&power_ups)
+        .turn_queue_depth(2)
+        .seed(seed)
+}
+
+/// The saved state of the game, but for the time `Game::advance()` has left over, which only
+/// smooths the drawing: the replays record the steps it runs.
+fn state(game: &Game) -> serde_json::Value {
+    let mut state: serde_json::Value = serde_json::from_str(&save::encode_json(game)).unwrap();
+    state.as_object_mut().unwrap().remove("accumulator");
+    state
+}
+
+/// Records a game through all the kinds of inputs, along with its state after each call; returns
+/// the states, as pairs of the number of frames recorded so far and the state.
+fn record(recorder: &mut Recorder) -> (Game, Vec<(usize, serde_json::Value)>) {
+    let mut game = recorder.create_game();
+    let mut states = Vec::new();
+    for call in 0..600_u32 {
+        if game.is_over() {
+            break;
+        }
+        if let Some(movement) = GreedyBot.next_move(&game) {
+            let _ = recorder.enqueue_turn(&mut game, 0, movement);
+        }
+        match call % 4 {
+            0 => recorder.process(&mut game, f64::from(5 + call % 23), None),
+            1 => {
+                let movement = GreedyBot.next_move(&game);
+                recorder.steer(&mut game, 0, movement);
+                recorder.process(&mut game, 16_f64, None);
+            }
+            2 => {
+                let movement = GreedyBot.next_move(&game);
+                recorder.process(&mut game, 9_f64, movement);
+            }
+            _ => {
+                recorder.advance(&mut game, f64::from(20 + call % 31), None);
+            }
+        }
+        states.push((recorder.get_replay().frame_count(), state(&game)));
+    }
+    (game, states)
+}
+
+fn assert_reproduces(recorder: &mut Recorder) {
+    let (game, states) = record(recorder);
+    let replay = Replay::decode_json(&recorder.get_replay().to_json()).unwrap();
+    assert_eq!(replay.frame_count(), recorder.get_replay().frame_count());
+    assert_eq!(state(&replay.run()), state(&game));
+
+    // Frame by frame, the replayed game goes through the same states.
+    let mut replayed = replay.create_game();
+    let mut applied = 0;
+    for (frames, expected) in states {
+        while applied < frames {
+            replay.apply_frame(&mut replayed, applied);
+            applied += 1;
+        }
+        assert_eq!(state(&replayed), expected, "frame {}", frames);
+    }
+}
+
+#[test]
+fn replay_reproduces_game() {
+    assert_reproduces(&mut Recorder::with_config(&config(21)).unwrap());
+}
+
+#[test]
+fn replay_reproduces_level_game() {
+    let mut recorder = Recorder::with_config(&config(22)).unwrap();
+    recorder.try_set_level(LEVEL).unwrap();
+    assert_reproduces(&mut recorder);
+}
+
+#[test]
+fn replay_picks_missing_seed() {
+    let replay = Recorder::with_config(&GameConfig::new(10, 8))
+        .unwrap()
+        .get_replay();
+    assert!(Replay::decode_json(&replay.to_json()).is_ok());
+}
+
+#[test]
+fn replay_rejects_invalid() {
+    let mut recorder = Recorder::with_config(&config(1)).unwrap();
+    let mut game = recorder.create_game();
+    recorder.process(&mut game, 16_f64, Some(Movement::DOWN));
+    let json = recorder.get_replay().to_json();
+    assert!(Replay::decode_json(&json).is_ok());
+    assert!(Replay::decode_json(&json.replace("\"version\":", "\"version\":999")).is_err());
+    let version = format!("\"version\":{}", REPLAY_VERSION);
+    let old = format!("\"version\":{}", MIN_REPLAY_VERSION - 1);
+    assert!(json.contains(&version));
+    assert!(Replay::decode_json(&json.replace(&version, &old)).is_err());
+    assert!(Replay::decode_json(&json.replace("\"seed\":1", "\"seed\":null")).is_err());
+    assert!(Replay::decode_json("{").is_err());
+}
@@ -0,0 +1,280 @@
+//! A saved game must restore exactly, and the saves of the older formats must keep loading.
+
+use rust_js_snake_game::bot::{Bot, GreedyBot};
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::difficulty::{SpeedCurve, SpeedDriver};
+use rust_js_snake_game::food::{BonusRules, FoodKind};
+use rust_js_snake_game::level::Level;
+use rust_js_snake_game::power_up::{PowerUpKind, PowerUpRules};
+use rust_js_snake_game::save::{self, SAVE_VERSION};
+use rust_js_snake_game::topology::Topology;
+use rust_js_snake_game::{Game, GameStatus};
+
+/// Saves of each older format, made by the version of the crate that introduced it, of the same
+/// game: 12x10, seed 42, with a snake that turns up and then left, played for 640 milliseconds.
+const FROZEN: &[(u32, &str, &[u8])] = &[
+    (
+        1,
+        include_str!("saves/v1.json"),
+        include_bytes!("saves/v1.bin"),
+    ),
+    (
+        2,
+        include_str!("saves/v2.json"),
+        include_bytes!("saves/v2.bin"),
+    ),
+    (
+        3,
+        include_str!("saves/v3.json"),
+        include_bytes!("saves/v3.bin"),
+    ),
+    (
+        4,
+        include_str!("saves/v4.json"),
+        include_bytes!("saves/v4.bin"),
+    ),
+    (
+        5,
+        include_str!("saves/v5.json"),
+        include_bytes!("saves/v5.bin"),
+    ),
+    (
+        6,
+        include_str!("saves/v6.json"),
+        include_bytes!("saves/v6.bin"),
+    ),
+    (
+        7,
+        include_str!("saves/v7.json"),
+        include_bytes!("saves/v7.bin"),
+    ),
+    (
+        8,
+        include_str!("saves/v8.json"),
+        include_bytes!("saves/v8.bin"),
+    ),
+    (
+        9,
+        include_str!("saves/v9.json"),
+        include_bytes!("saves/v9.bin"),
+    ),
+    (
+        10,
+        include_str!("saves/v10.json"),
+        include_bytes!("saves/v10.bin"),
+    ),
+    (
+        11,
+        include_str!("saves/v11.json"),
+        include_bytes!("saves/v11.bin"),
+    ),
+];
+
+const LEVEL: &str = "\
+..............
+..#.......#...
+..#..S..>.#...
+..#.......#...
+..............
+......##......
+..............
+..............
+..............
+..............";
+
+/// A game using all the saved settings: walls, a toroidal board, several food kinds, the bonus,
+/// power-ups, a speed curve, a deeper turn queue and a fixed step.
+fn rich_game() -> Game {
+    let power_ups = PowerUpRules::new(300_f64, 900_f64, 1500_f64)
+        .kind(&PowerUpKind::speed(1.5, 600_f64, 1_f64))
+        .kind(&PowerUpKind::ghost(800_f64, 1_f64))
+        .kind(&PowerUpKind::shrink(1, 1_f64))
+        .kind(&PowerUpKind::score_multiplier(2, 900_f64, 1_f64));
+    let config = GameConfig::new(14, 10)
+        .topology(Topology::Toroidal)
+        .food_count(3)
+        .food_kind(&FoodKind::new(1, 1, Some(2000_f64), 1_f64))
+        .food_kind(&FoodKind::new(3, 2, None, 0.5))
+        .bonus(&BonusRules::new(500_f64, 1500_f64, 1200_f64, 5, 1))
+        .power_ups(&power_ups)
+        .speed_curve(&SpeedCurve::linear(SpeedDriver::Time, 5000_f64, 0.001).capped(0.01))
+        .turn_queue_depth(3);
+    let mut game = Game::with_level_config(&Level::parse(LEVEL).unwrap(), &config, 17);
+    game.fixed_step = 12.5;
+    game
+}
+
+/// Plays the given number of frames of uneven length, steered by a bot.
+fn play(game: &mut Game, frames: u32) {
+    for frame in 0..frames {
+        if game.is_over() {
+            return;
+        }
+        if let Some(movement) = GreedyBot.next_move(game) {
+            let _ = game.push_turn(0, movement);
+        }
+        game.advance(f64::from(10 + frame % 13), None);
+    }
+}
+
+fn assert_restores(game: &Game) {
+    let json = save::encode_json(game);
+    let bytes = save::encode_binary(game);
+    let from_json = save::decode_json(&json).unwrap();
+    let from_bytes = save::decode_binary(&bytes).unwrap();
+    assert_eq!(save::encode_json(&from_json), json);
+    assert_eq!(save::encode_binary(&from_bytes), bytes);
+
+    // The state that isn't saved, such as the occupancy, must be rebuilt the same.
+    let mut original = save::decode_json(&json).unwrap();
+    let mut restored = [from_json, from_bytes];
+    play(&mut original, 150);
+    for game in &mut restored {
+        play(game, 150);
+        assert_eq!(save::encode_json(game), save::encode_json(&original));
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut game = rich_game();
+    // Saved at many points, so that some of them are in the middle of growing, with turns queued,
+    // items on the board and effects running.
+    for _ in 0..60 {
+        assert_restores(&game);
+        play(&mut game, 7);
+    }
+    assert_eq!(game.status(), GameStatus::Running);
+}
+
+#[test]
+fn round_trip_over() {
+    let mut game = rich_game();
+    play(&mut game, 100_000);
+    assert!(game.is_over());
+    assert_restores(&game);
+}
+
+/// The state of the frozen game, with the positions rounded, since the older versions moved the
+/// snake with different rounding errors.
+fn summary(game: &Game) -> String {
+    let round = |value: f64| format!("{:.6}", value + 0_f64);
+    let snakes: Vec<String> = game
+        .snakes()
+        .iter()
+        .map(|snake| {
+            let body: Vec<String> = snake
+                .body()
+                .iter()
+                .map(|point| format!("({}, {})", round(point.x), round(point.y)))
+                .collect();
+            format!("{} {} [{}]", snake.score, snake.alive, body.join(" "))
+        })
+        .collect();
+    let foods: Vec<String> = game
+        .foods()
+        .iter()
+        .map(|food| format!("({}, {})", food.position.x, food.position.y))
+        .collect();
+    format!(
+        "{:?}; seed: {}; snakes: {}; foods: {}",
+        game.status(),
+        game.seed(),
+        snakes.join(", "),
+        foods.join(" ")
+    )
+}
+
+#[test]
+fn frozen_saves_load() {
+    assert_eq!(
+        FROZEN.len() as u32,
+        SAVE_VERSION - 1,
+        "a save of each older format"
+    );
+    let expected = "Running; seed: 42; snakes: 0 true [(6.500000, 2.100000) (6.500000, 0.500000) \
+                    (5.100000, 0.500000)]; foods: (9.5, 4.5)";
+    for &(version, json, bytes) in FROZEN {
+        let mut from_json = save::decode_json(json)
+            .unwrap_or_else(|error| panic!("version {}: {}", version, error));
+        let mut from_bytes = save::decode_binary(bytes)
+            .unwrap_or_else(|error| panic!("version {}: {}", version, error));
+        assert_eq!(summary(&from_json), expected, "version {}", version);
+        assert_eq!(
+            save::encode_json(&from_bytes),
+            save::encode_json(&from_json),
+            "version {}",
+            version
+        );
+
+        // The games go on the same, whatever format they've been loaded from.
+        let mut current = save::decode_json(FROZEN[FROZEN.len() - 1].1).unwrap();
+        for game in [&mut from_json, &mut from_bytes, &mut current] {
+            play(game, 300);
+        }
+        assert_eq!(
+            summary(&from_json),
+            summary(&current),
+            "version {}",
+            version
+        );
+        assert_eq!(
+            summary(&from_bytes),
+            summary(&current),
+            "version {}",
+            version
+        );
+    }
+}
+
+/// Power-up kinds were saved as a closed set of effects until version 11.
+#[test]
+fn frozen_power_ups_load() {
+    let mut game = rich_game();
+    play(&mut game, 200);
+    let from_json = save::decode_json(include_str!("saves/v11-power-ups.json")).unwrap();
+    let from_bytes = save::decode_binary(include_bytes!("saves/v11-power-ups.bin")).unwrap();
+    assert_eq!(save::encode_json(&from_json), save::encode_json(&game));
+    assert_eq!(save::encode_json(&from_bytes), save::encode_json(&game));
+}
+
+/// Saves that can't have been made by a game are rejected, rather than loaded into one that
+/// misbehaves.
+#[test]
+fn invalid_saves() {
+    let mut game = rich_game();
+    while game.active_effects().is_empty() || game.power_up().is_none() {
+        play(&mut game, 1);
+    }
+    let json: serde_json::Value = serde_json::from_str(&save::encode_json(&game)).unwrap();
+    assert!(save::decode_json(&json.to_string()).is_ok());
+    let changes: &[(&str, serde_json::Value)] = &[
+        ("/speed", (-0.01).into()),
+        ("/speed", 0.into()),
+        ("/turn_queue_depth", 0.into()),
+        ("/walls/0/0", 14.into()),
+        ("/walls/0/1", (-1).into()),
+        ("/foods/0/position/x", 14.5.into()),
+        ("/power_up/position/y", (-0.5).into()),
+        ("/effects/0/remaining", (-1).into()),
+    ];
+    for (pointer, value) in changes {
+        let mut invalid = json.clone();
+        *invalid.pointer_mut(pointer).unwrap() = value.clone();
+        assert!(
+            save::decode_json(&invalid.to_string()).is_err(),
+            "{}: {}",
+            pointer,
+            value
+        );
+    }
+}
+
+#[test]
+fn unsupported_version() {
+    let json = FROZEN[0].1.replace("\"version\":1", "\"version\":999");
+    assert!(save::decode_json(&json).is_err());
+    let mut bytes = FROZEN[0].2.to_vec();
+    bytes[..4].copy_from_slice(&999_u32.to_le_bytes());
+    assert!(save::decode_binary(&bytes).is_err());
+}
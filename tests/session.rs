@@ -0,0 +1,127 @@
+//! The session moves between its states only through the allowed actions, and keeps the paused
+//! time out of the game.
+
+use rust_js_snake_game::bot::{Bot, HamiltonianBot};
+use rust_js_snake_game::config::{ConfigError, GameConfig};
+use rust_js_snake_game::level::{LevelError, LevelErrorKind};
+use rust_js_snake_game::session::{Session, SessionAction, SessionError, SessionState};
+
+fn config() -> GameConfig {
+    GameConfig::new(10, 8).seed(7)
+}
+
+fn invalid(state: SessionState, action: SessionAction) -> Result<(), SessionError> {
+    Err(SessionError::InvalidTransition { state, action })
+}
+
+#[test]
+fn transitions() {
+    let mut session = Session::with_config(&config(), None).unwrap();
+    let mut game = session.create_game();
+    assert_eq!(session.state(), SessionState::Ready);
+    assert_eq!(
+        session.apply(SessionAction::Pause, 0_f64),
+        invalid(SessionState::Ready, SessionAction::Pause)
+    );
+    assert!(session.try_update(&mut game, 100_f64, None).is_err());
+
+    session.apply(SessionAction::Start, 1000_f64).unwrap();
+    assert_eq!(session.state(), SessionState::Running);
+    assert_eq!(
+        session.apply(SessionAction::Start, 1000_f64),
+        invalid(SessionState::Running, SessionAction::Start)
+    );
+    assert_eq!(
+        session.apply(SessionAction::Resume, 1000_f64),
+        invalid(SessionState::Running, SessionAction::Resume)
+    );
+    assert_eq!(session.try_update(&mut game, 1100_f64, None), Ok(10));
+
+    session.apply(SessionAction::Pause, 1150_f64).unwrap();
+    assert_eq!(session.state(), SessionState::Paused);
+    assert_eq!(
+        session.try_update(&mut game, 1200_f64, None),
+        Err(SessionError::InvalidTransition {
+            state: SessionState::Paused,
+            action: SessionAction::Update,
+        })
+    );
+    // The 10 seconds spent paused don't count.
+    session.apply(SessionAction::Resume, 11150_f64).unwrap();
+    assert_eq!(session.state(), SessionState::Running);
+    assert_eq!(session.try_update(&mut game, 11200_f64, None), Ok(10));
+    assert_eq!(session.get_replay().frame_count(), 20);
+}
+
+#[test]
+fn game_over() {
+    let mut session = Session::with_config(&config(), None).unwrap();
+    let mut game = session.create_game();
+    session.apply(SessionAction::Start, 0_f64).unwrap();
+    session.try_update(&mut game, 10000_f64, None).unwrap();
+    assert_eq!(session.state(), SessionState::GameOver);
+    assert_eq!(
+        session.apply(SessionAction::Pause, 10000_f64),
+        invalid(SessionState::GameOver, SessionAction::Pause)
+    );
+    assert!(session.try_update(&mut game, 10100_f64, None).is_err());
+}
+
+#[test]
+fn won() {
+    let config = GameConfig::new(6, 4).speed(0.03).snake_length(2).seed(7);
+    let mut session = Session::with_config(&config, None).unwrap();
+    let mut game = session.create_game();
+    let mut bot = HamiltonianBot::for_game(&game).unwrap();
+    session.apply(SessionAction::Start, 0_f64).unwrap();
+    let mut now = 0_f64;
+    while session.state() == SessionState::Running && now < 600_000_f64 {
+        now += 16_f64;
+        let movement = bot.next_move(&game);
+        session.try_update(&mut game, now, movement).unwrap();
+    }
+    assert_eq!(session.state(), SessionState::Won);
+    assert_eq!(
+        session.apply(SessionAction::Pause, now),
+        invalid(SessionState::Won, SessionAction::Pause)
+    );
+}
+
+#[test]
+fn restart() {
+    let mut session = Session::with_config(&config(), None).unwrap();
+    assert!(session.last_replay().is_none());
+    let mut game = session.create_game();
+    session.apply(SessionAction::Start, 0_f64).unwrap();
+    session.try_update(&mut game, 500_f64, None).unwrap();
+    let played = game.to_json();
+
+    let game = session.restart();
+    assert_eq!(session.state(), SessionState::Ready);
+    assert_eq!(session.get_replay().frame_count(), 0);
+    let replay = session.last_replay().unwrap();
+    assert_eq!(replay.frame_count(), 50);
+    assert_eq!(replay.run().to_json(), played);
+    // The config has a seed, so the new game is the same as the first one was.
+    assert_eq!(game.to_json(), session.create_game().to_json());
+    assert_eq!(game.to_json(), replay.create_game().to_json());
+}
+
+#[test]
+fn invalid_settings() {
+    assert!(matches!(
+        Session::with_config(&GameConfig::new(0, 8), None),
+        Err(SessionError::Config(ConfigError::EmptyBoard { .. }))
+    ));
+    let error = Session::with_config(&config(), Some("S..".to_string()))
+        .err()
+        .unwrap();
+    assert_eq!(
+        error,
+        SessionError::Level(LevelError {
+            line: 2,
+            column: 1,
+            kind: LevelErrorKind::MissingHead,
+        })
+    );
+}
@@ -0,0 +1,66 @@
+//! Queued turns are checked against the direction the snake will have once the turns before them
+//! are applied.
+
+use rust_js_snake_game::config::GameConfig;
+use rust_js_snake_game::{Game, Movement, TurnRejection};
+
+fn game(turn_queue_depth: usize) -> Game {
+    let config = GameConfig::new(20, 20).turn_queue_depth(turn_queue_depth);
+    Game::with_config(&config).unwrap()
+}
+
+#[test]
+fn rejections() {
+    let mut game = game(2);
+    assert_eq!(
+        game.push_turn(0, Movement::RIGHT),
+        Err(TurnRejection::Redundant)
+    );
+    assert_eq!(
+        game.push_turn(0, Movement::LEFT),
+        Err(TurnRejection::Opposite)
+    );
+    assert_eq!(game.push_turn(0, Movement::DOWN), Ok(()));
+    assert_eq!(
+        game.push_turn(0, Movement::DOWN),
+        Err(TurnRejection::Redundant)
+    );
+    assert_eq!(
+        game.push_turn(0, Movement::TOP),
+        Err(TurnRejection::Opposite)
+    );
+    assert_eq!(game.push_turn(0, Movement::LEFT), Ok(()));
+    assert_eq!(game.queued_turns(0), 2);
+    // The queue is full, even if the turn would be valid after the queued ones.
+    assert_eq!(
+        game.push_turn(0, Movement::DOWN),
+        Err(TurnRejection::QueueFull)
+    );
+    assert_eq!(
+        game.enqueue_turn(0, Movement::DOWN),
+        Some(TurnRejection::QueueFull)
+    );
+    assert_eq!(game.queued_turns(0), 2);
+}
+
+#[test]
+fn applied_turns_free_the_queue() {
+    let mut game = game(1);
+    assert_eq!(game.enqueue_turn(0, Movement::DOWN), None);
+    assert_eq!(
+        game.enqueue_turn(0, Movement::LEFT),
+        Some(TurnRejection::QueueFull)
+    );
+
+    // A cell takes about 167 milliseconds.
+    game.process(200_f64, None);
+    assert_eq!(game.queued_turns(0), 0);
+    assert!(game.snakes()[0]
+        .direction
+        .equal_to(&Movement::DOWN.direction()));
+    assert_eq!(
+        game.enqueue_turn(0, Movement::TOP),
+        Some(TurnRejection::Opposite)
+    );
+    assert_eq!(game.enqueue_turn(0, Movement::LEFT), None);
+}
@@ -5,5 +5,36 @@ export default {
   SNAKE_LENGTH: 3,
   SNAKE_DIRECTION_X: 1,
   SNAKE_DIRECTION_Y: 0,
+  PLAYERS: 1,
+  TOROIDAL: false,
+  // Level map (see `src/level.rs` for the format); overrides the board size and the snake.
+  LEVEL: undefined,
+  // Maximum number of turns that can be pressed ahead.
+  TURN_QUEUE_DEPTH: 3,
+  // Food items on the board at once.
+  FOOD_COUNT: 1,
+  // Bonus food, appearing every few seconds and worth more the sooner it's eaten; `undefined` to
+  // disable it. Times are in milliseconds.
+  BONUS: {
+    MIN_INTERVAL: 10000,
+    MAX_INTERVAL: 20000,
+    LIFETIME: 5000,
+    MAX_SCORE: 5,
+    GROWTH: 1
+  },
+  // Power-ups, appearing every few seconds, whose effects last for a while once collected;
+  // `undefined` to disable them. Times are in milliseconds.
+  POWER_UPS: {
+    MIN_INTERVAL: 15000,
+    MAX_INTERVAL: 30000,
+    LIFETIME: 6000,
+    SPEED_BOOST: { FACTOR: 1.5, DURATION: 5000, WEIGHT: 1 },
+    SLOWDOWN: { FACTOR: 0.6, DURATION: 5000, WEIGHT: 1 },
+    GHOST: { DURATION: 5000, WEIGHT: 1 },
+    SHRINK: { UNITS: 3, WEIGHT: 1 },
+    SCORE_MULTIPLIER: { FACTOR: 2, DURATION: 10000, WEIGHT: 1 }
+  },
+  // Length of the simulation steps, in milliseconds; rendering is interpolated between them.
+  FIXED_STEP: 10,
   FPS: 60
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 import { Movement } from "wasm-snake-game";
 
+// The first key of each movement (WASD) belongs to the first player, the second one (arrows) to
+// the second player; in single player games, both belong to the only player.
 const MOVEMENT_KEYS = {
   [Movement.TOP]: [87, 38],
   [Movement.RIGHT]: [68, 39],
@@ -9,16 +11,30 @@ const MOVEMENT_KEYS = {
 
 const STOP_KEY = 32
 
+const findMovement = which => Object.keys(MOVEMENT_KEYS).find(key => MOVEMENT_KEYS[key].includes(which))
+
+const findPlayer = which => Object.values(MOVEMENT_KEYS).map(keys => keys.indexOf(which)).find(player => player !== -1)
+
 export class Controller {
   constructor(onStop = () => { }) {
+    // Turns pressed since the last `takeTurns()`, in order, as `{ player, movement }`.
+    this.turns = []
     window.addEventListener('keydown', ({ which }) => {
-      this.movement = Object.keys(MOVEMENT_KEYS).find(key => MOVEMENT_KEYS[key].includes(which))
+      const movement = findMovement(which)
+      if (movement !== undefined) {
+        this.turns.push({ player: findPlayer(which), movement: Number(movement) })
+      }
     })
     window.addEventListener('keyup', ({ which }) => {
-      this.movement = undefined
       if (which === STOP_KEY) {
         onStop()
       }
     })
   }
-}
\ No newline at end of file
+
+  takeTurns() {
+    const turns = this.turns
+    this.turns = []
+    return turns
+  }
+}
@@ -1,4 +1,17 @@
-import { Game, Vector } from 'wasm-snake-game'
+import {
+  Autopilot,
+  BonusRules,
+  BotKind,
+  Difficulty,
+  GameConfig,
+  PowerUpKind,
+  PowerUpRules,
+  Replay,
+  Session,
+  SessionState,
+  Topology,
+  Vector
+} from 'wasm-snake-game'
 
 import CONFIG from './config'
 import { View } from './view'
@@ -7,7 +20,47 @@ import Storage from './storage'
 
 export class GameManager {
   constructor() {
-    this.restart()
+    // A difficulty preset replaces the board size, speed, snake length and food of the config.
+    const difficulty = Storage.getDifficulty()
+    const isPreset = difficulty in Difficulty
+    let config = isPreset
+      ? GameConfig.preset(Difficulty[difficulty])
+      : new GameConfig(CONFIG.WIDTH, CONFIG.HEIGHT)
+        .speed(CONFIG.SPEED)
+        .snake_length(CONFIG.SNAKE_LENGTH)
+        .food_count(CONFIG.FOOD_COUNT)
+    config = config
+      .direction(new Vector(CONFIG.SNAKE_DIRECTION_X, CONFIG.SNAKE_DIRECTION_Y))
+      .players(CONFIG.PLAYERS)
+      .topology(CONFIG.TOROIDAL ? Topology.Toroidal : Topology.Bounded)
+      .turn_queue_depth(CONFIG.TURN_QUEUE_DEPTH)
+    if (CONFIG.BONUS && !isPreset) {
+      const { MIN_INTERVAL, MAX_INTERVAL, LIFETIME, MAX_SCORE, GROWTH } = CONFIG.BONUS
+      config = config.bonus(
+        new BonusRules(MIN_INTERVAL, MAX_INTERVAL, LIFETIME, MAX_SCORE, GROWTH)
+      )
+    }
+    if (CONFIG.POWER_UPS) {
+      const {
+        MIN_INTERVAL, MAX_INTERVAL, LIFETIME, SPEED_BOOST, SLOWDOWN, GHOST, SHRINK, SCORE_MULTIPLIER
+      } = CONFIG.POWER_UPS
+      const rules = new PowerUpRules(MIN_INTERVAL, MAX_INTERVAL, LIFETIME)
+        .kind(PowerUpKind.speed(SPEED_BOOST.FACTOR, SPEED_BOOST.DURATION, SPEED_BOOST.WEIGHT))
+        .kind(PowerUpKind.speed(SLOWDOWN.FACTOR, SLOWDOWN.DURATION, SLOWDOWN.WEIGHT))
+        .kind(PowerUpKind.ghost(GHOST.DURATION, GHOST.WEIGHT))
+        .kind(PowerUpKind.shrink(SHRINK.UNITS, SHRINK.WEIGHT))
+        .kind(
+          PowerUpKind.score_multiplier(
+            SCORE_MULTIPLIER.FACTOR,
+            SCORE_MULTIPLIER.DURATION,
+            SCORE_MULTIPLIER.WEIGHT
+          )
+        )
+      config = config.power_ups(rules)
+    }
+    // Throws if the config or the level are invalid; each game gets a random seed.
+    this.session = new Session(config, CONFIG.LEVEL)
+    this.startGame(this.session.create_game())
     this.view = new View(
       this.game.width,
       this.game.height,
@@ -16,58 +69,181 @@ export class GameManager {
     this.controller = new Controller(
       this.onStop.bind(this)
     )
+    document.getElementById('download-replay').addEventListener(
+      'click',
+      this.downloadReplay.bind(this)
+    )
+    document.getElementById('upload-replay').addEventListener(
+      'change',
+      ({ target }) => {
+        const [file] = target.files
+        // Cleared, so that picking the same file again plays it again.
+        target.value = ''
+        if (file) {
+          file.text().then(this.playReplay.bind(this))
+        }
+      }
+    )
+    document.getElementById('autopilot').addEventListener(
+      'change',
+      ({ target }) => this.setAutopilot(target.checked)
+    )
+    this.setUpDifficulty(isPreset ? difficulty : '')
   }
 
-  restart() {
-    this.game = new Game(
-      CONFIG.WIDTH,
-      CONFIG.HEIGHT,
-      CONFIG.SPEED,
-      CONFIG.SNAKE_LENGTH,
-      new Vector(
-        CONFIG.SNAKE_DIRECTION_X,
-        CONFIG.SNAKE_DIRECTION_Y
-      )
-    )
-    this.lastUpdate = undefined
-    this.stopTime = undefined
+  // The presets change the board size, so the page is reloaded to apply them.
+  setUpDifficulty(current) {
+    const select = document.getElementById('difficulty')
+    GameConfig.presets().forEach(({ difficulty, config }) => {
+      const option = document.createElement('option')
+      option.value = difficulty
+      option.innerText = `${difficulty} (${config.width}x${config.height})`
+      select.appendChild(option)
+    })
+    select.value = current
+    select.addEventListener('change', ({ target }) => {
+      Storage.setDifficulty(target.value)
+      location.reload()
+    })
+  }
+
+  setAutopilot(enabled) {
+    this.autopilot = enabled
+      ? new Autopilot(BotKind.AStar, this.game)
+      : undefined
+  }
+
+  startGame(game) {
+    this.game = game
+    this.game.fixed_step = CONFIG.FIXED_STEP
+    this.session.start(Date.now())
   }
 
   onStop() {
-    const now = Date.now()
-    if (this.stopTime) {
-      this.stopTime = undefined
-      this.lastUpdate = this.time + now - this.lastUpdate
-    } else {
-      this.stopTime = now
+    if (this.playback) {
+      this.stopReplay()
+      return
+    }
+    switch (this.session.state()) {
+      case SessionState.Won:
+        this.view.setVictory(false)
+        this.startGame(this.session.restart())
+        break
+      case SessionState.Running:
+        this.session.pause(Date.now())
+        break
+      case SessionState.Paused:
+        this.session.resume(Date.now())
+        break
+    }
+  }
+
+  downloadReplay() {
+    const replay = this.session.last_replay() || this.session.get_replay()
+    const link = document.createElement('a')
+    link.href = URL.createObjectURL(
+      new Blob([replay.to_json()], { type: 'application/json' })
+    )
+    link.download = 'snake-replay.json'
+    link.click()
+    URL.revokeObjectURL(link.href)
+  }
+
+  // Plays the uploaded replay back in real time, in place of the game, which is paused until the
+  // playback is stopped.
+  playReplay(json) {
+    let replay
+    try {
+      replay = Replay.from_json(json)
+    } catch (error) {
+      alert(`The replay can't be played: ${error}`)
+      return
+    }
+    if (this.playback) {
+      this.stopReplay()
+    }
+    const resume = this.session.state() === SessionState.Running
+    if (resume) {
+      this.session.pause(Date.now())
+    }
+    const game = replay.create_game()
+    this.playback = { replay, game, resume, frame: 0, time: 0, start: Date.now() }
+    this.view.setPlayback(true)
+    this.view.setBoardSize(game.width, game.height)
+  }
+
+  stopReplay() {
+    const { resume } = this.playback
+    this.playback = undefined
+    this.view.setPlayback(false)
+    this.view.setBoardSize(this.game.width, this.game.height)
+    if (resume) {
+      this.session.resume(Date.now())
+    }
+  }
+
+  // Applies the frames of the replay that fit in the time since the playback started; once they
+  // have all been applied, the end of the game stays on screen.
+  tickPlayback() {
+    const playback = this.playback
+    const elapsed = Date.now() - playback.start
+    while (playback.frame < playback.replay.frame_count() && playback.time < elapsed) {
+      playback.time += playback.replay.frame_timespan(playback.frame)
+      playback.replay.apply_frame(playback.game, playback.frame)
+      playback.frame += 1
     }
   }
 
   render() {
+    const game = this.playback ? this.playback.game : this.game
+    const alpha = game.step_alpha()
+    // The buffers are views into the wasm memory, which any allocation can detach, so they're
+    // passed as getters, called right before each one is read.
     this.view.render(
-      this.game.food,
-      this.game.get_snake(),
-      this.game.score,
-      Storage.getBestScore()
+      () => game.get_food_buffer(),
+      game.bonus(),
+      game.bonus_score(),
+      game.power_up(),
+      game.players(),
+      player => game.interpolated_player_snake_pieces(player, alpha),
+      game.get_walls(),
+      game.score,
+      Storage.getBestScore(),
+      game.get_active_effects()
     )
   }
 
   tick() {
-    if (!this.stopTime) {
-      const lastUpdate = Date.now()
-      if (this.lastUpdate) {
-        this.game.process(lastUpdate - this.lastUpdate, this.controller.movement)
-        if (this.game.is_over()) {
-          this.restart()
-          return
-        }
-        if (this.game.score > Storage.getBestScore()) {
-          Storage.setBestScore(this.game.score)
-        }
-      }
-      this.lastUpdate = lastUpdate
+    if (this.playback) {
+      this.tickPlayback()
       this.render()
+      return
+    }
+    if (this.session.state() !== SessionState.Running) {
+      return
+    }
+    // In multiplayer games, the keys are split between the players.
+    this.controller.takeTurns().forEach(({ player, movement }) =>
+      this.session.enqueue_turn(this.game, CONFIG.PLAYERS > 1 ? player : 0, movement)
+    )
+    const movement = this.autopilot ? this.autopilot.next_move(this.game) : undefined
+    // Long frames (e.g. after the tab was in the background) are run as several short steps.
+    this.session.update(this.game, Date.now(), movement)
+    this.game.take_events()
+      .filter(({ type, new_score }) =>
+        (type === 'FoodEaten' || type === 'BonusEaten') && new_score > Storage.getBestScore()
+      )
+      .forEach(({ new_score }) => Storage.setBestScore(new_score))
+    switch (this.session.state()) {
+      case SessionState.Won:
+        // Keep the full board on screen, until the player restarts.
+        this.view.setVictory(true)
+        break
+      case SessionState.GameOver:
+        this.startGame(this.session.restart())
+        break
     }
+    this.render()
   }
 
   run() {
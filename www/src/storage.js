@@ -2,5 +2,10 @@ export default {
   getBestScore: () => parseInt(localStorage.bestScore) || 0,
   setBestScore: (bestScore) => {
     localStorage.setItem('bestScore', bestScore)
+  },
+  // Name of the difficulty preset; empty for the settings of the config.
+  getDifficulty: () => localStorage.difficulty || '',
+  setDifficulty: (difficulty) => {
+    localStorage.setItem('difficulty', difficulty)
   }
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 const getRange = length => [...Array(length).keys()]
 
+const SNAKE_COLORS = ['#3498db', '#2ecc71', '#9b59b6', '#f1c40f']
+
 export class View {
   constructor(gameWidth, gameHeight, onViewChange = () => { }) {
     this.gameWidth = gameWidth
@@ -8,14 +10,23 @@ export class View {
     this.onViewChange = onViewChange
     this.setUp()
 
-    window.addEventListener('resize', () => {
-      const [child] = this.container.children
-      if (child) {
-        this.container.removeChild(child)
-      }
-      this.setUp()
-      this.onViewChange()
-    })
+    window.addEventListener('resize', () => this.reset())
+  }
+
+  // Fits the view to a board of another size, such as the one of a replay.
+  setBoardSize(gameWidth, gameHeight) {
+    this.gameWidth = gameWidth
+    this.gameHeight = gameHeight
+    this.reset()
+  }
+
+  reset() {
+    const [child] = this.container.children
+    if (child) {
+      this.container.removeChild(child)
+    }
+    this.setUp()
+    this.onViewChange()
   }
 
   setUp() {
@@ -25,7 +36,6 @@ export class View {
       height / this.gameHeight
     )
     this.projectDistance = distance => distance * this.unitOnScreen
-    this.projectPosition = position => position.scale_by(this.unitOnScreen)
 
     const canvas = document.createElement('canvas')
     this.container.appendChild(canvas)
@@ -34,7 +44,18 @@ export class View {
     canvas.setAttribute('height', this.projectDistance(this.gameHeight))
   }
 
-  render(food, snake, score, bestScore) {
+  render(
+    getFood,
+    bonus,
+    bonusScore,
+    powerUp,
+    players,
+    getSnakePieces,
+    walls,
+    score,
+    bestScore,
+    effects
+  ) {
     this.context.clearRect(
       0,
       0,
@@ -58,27 +79,97 @@ export class View {
     )
     this.context.globalAlpha = 1
 
-    const projectedFood = this.projectPosition(food)
-    this.context.beginPath()
-    this.context.arc(
-      projectedFood.x,
-      projectedFood.y,
-      this.unitOnScreen / 2.5,
-      0,
-      2 * Math.PI
+    this.context.fillStyle = '#7f8c8d'
+    walls.forEach(({ x, y }) =>
+      this.context.fillRect(
+        (x - 0.5) * this.unitOnScreen,
+        (y - 0.5) * this.unitOnScreen,
+        this.unitOnScreen,
+        this.unitOnScreen
+      )
     )
+
+    // Interleaved coordinates; there's no food once the board is full.
+    const food = getFood()
     this.context.fillStyle = '#e74c3c'
-    this.context.fill()
+    for (let index = 0; index < food.length; index += 2) {
+      this.context.beginPath()
+      this.context.arc(
+        this.projectDistance(food[index]),
+        this.projectDistance(food[index + 1]),
+        this.unitOnScreen / 2.5,
+        0,
+        2 * Math.PI
+      )
+      this.context.fill()
+    }
+
+    if (bonus) {
+      const { x, y } = bonus.position
+      this.context.beginPath()
+      this.context.arc(
+        this.projectDistance(x),
+        this.projectDistance(y),
+        this.unitOnScreen / 2.5,
+        0,
+        2 * Math.PI
+      )
+      this.context.fillStyle = '#f1c40f'
+      this.context.fill()
+      // What it's worth right now.
+      this.context.fillStyle = 'black'
+      this.context.textAlign = 'center'
+      this.context.textBaseline = 'middle'
+      this.context.fillText(bonusScore, this.projectDistance(x), this.projectDistance(y))
+    }
+
+    if (powerUp) {
+      const { x, y } = powerUp.position
+      const half = this.unitOnScreen / 2.5
+      this.context.fillStyle = '#e67e22'
+      this.context.beginPath()
+      this.context.moveTo(this.projectDistance(x), this.projectDistance(y) - half)
+      this.context.lineTo(this.projectDistance(x) + half, this.projectDistance(y))
+      this.context.lineTo(this.projectDistance(x), this.projectDistance(y) + half)
+      this.context.lineTo(this.projectDistance(x) - half, this.projectDistance(y))
+      this.context.fill()
+    }
 
     this.context.lineWidth = this.unitOnScreen
-    this.context.strokeStyle = '#3498db'
-    this.context.beginPath()
-    snake
-      .map(this.projectPosition)
-      .forEach(({ x, y }) => this.context.lineTo(x, y))
-    this.context.stroke()
+    for (let player = 0; player < players; player++) {
+      this.context.strokeStyle = SNAKE_COLORS[player % SNAKE_COLORS.length]
+      // Interleaved coordinates, with a NaN pair between the pieces.
+      const pieces = getSnakePieces(player)
+      this.context.beginPath()
+      for (let index = 0; index < pieces.length; index += 2) {
+        if (Number.isNaN(pieces[index])) {
+          this.context.stroke()
+          this.context.beginPath()
+        } else {
+          this.context.lineTo(
+            this.projectDistance(pieces[index]),
+            this.projectDistance(pieces[index + 1])
+          )
+        }
+      }
+      this.context.stroke()
+    }
 
     document.getElementById('current-score').innerText = score
     document.getElementById('best-score').innerText = bestScore
+    document.getElementById('effects').innerText = effects
+      .map(({ name, player, remaining, stacks }) =>
+        `${players > 1 ? `P${player + 1} ` : ''}${name}${stacks > 1 ? ` x${stacks}` : ''}` +
+        ` ${Math.ceil(remaining / 1000)}s`
+      )
+      .join(', ')
+  }
+
+  setVictory(visible) {
+    document.getElementById('victory').hidden = !visible
+  }
+
+  setPlayback(visible) {
+    document.getElementById('playback').hidden = !visible
   }
 }
\ No newline at end of file